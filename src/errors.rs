@@ -0,0 +1,133 @@
+//! Typed Python exception hierarchy and helpers to raise it from Rust.
+//!
+//! `unwrap_any!`/`try_unwrap!` (see [`crate::macros`]) usually propagate
+//! Rust errors to Python by calling [`std::panic::panic_any`], which
+//! `timely` then needs a `catch_unwind` to turn back into a `PyErr` -- see
+//! [`crate::execution::unwrap_worker_panic`]. That dance is the only option
+//! for errors that have to cross a worker thread boundary, but it's
+//! overkill (and surfaces as an opaque, uncatchable panic) for errors
+//! raised directly from a Python call, like a config constructor or a
+//! pickle hook, where a normal `PyResult::Err` would do.
+//!
+//! This module defines a hierarchy of catchable, documented exceptions for
+//! that case: [`BytewaxError`] is the common base, with [`DataflowError`],
+//! [`RecoveryError`], and [`WindowConfigError`] as subclasses callers can
+//! catch specifically. [`py_wrap_error!`] wires a Rust error type to one of
+//! these via `impl From<_> for PyErr`, and `try_unwrap!(expr => ExcType)`
+//! (see [`crate::try_unwrap`]) does the same inline for call sites that
+//! don't want a blanket `From` impl. Both preserve the error's
+//! [`std::fmt::Display`] message and chain its source as `__cause__`.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+create_exception!(
+    bytewax,
+    BytewaxError,
+    PyException,
+    "Base class for all exceptions raised by bytewax's Rust core."
+);
+
+create_exception!(
+    bytewax,
+    DataflowError,
+    BytewaxError,
+    "Raised when a dataflow can't be built or run as specified."
+);
+
+create_exception!(
+    bytewax,
+    RecoveryError,
+    BytewaxError,
+    "Raised when a recovery store can't be read from or written to."
+);
+
+create_exception!(
+    bytewax,
+    WindowConfigError,
+    BytewaxError,
+    "Raised when a window configuration is invalid."
+);
+
+/// Build a `$py_exc` from `err`, preserving its `Display` message and
+/// chaining `err.source()` (if any) as `__cause__`.
+///
+/// Shared by [`py_wrap_error!`] and the `try_unwrap!(expr => $py_exc)`
+/// macro arm so both raise exceptions that look the same regardless of
+/// whether the conversion happens through a `From` impl or inline.
+#[doc(hidden)]
+pub fn wrap_error<E>(err: &(dyn std::error::Error + 'static)) -> PyErr
+where
+    E: pyo3::PyTypeInfo,
+{
+    let py_err = PyErr::new::<E, _>(err.to_string());
+    if let Some(source) = err.source() {
+        Python::with_gil(|py| {
+            py_err.set_cause(py, Some(PyErr::new::<PyException, _>(source.to_string())));
+        });
+    }
+    py_err
+}
+
+#[macro_export]
+/// Implements `From<$rust_err> for PyErr` so that the `?` operator in a
+/// `PyResult`-returning function raises `$py_exc` -- preserving the
+/// `Display` message and `__cause__`, see [`crate::errors::wrap_error`] --
+/// instead of whatever generic conversion PyO3 would otherwise pick.
+///
+/// In the spirit of rigetti-pyo3's `py_wrap_error!` and PyO3's
+/// `create_exception!`. `$rust_err` must be a type local to this crate
+/// (Rust's orphan rules forbid `impl From<TheirType> for PyErr` here), so
+/// this is meant for bytewax's own error enums/structs, not a dependency's.
+///
+/// ```rust
+/// use bytewax::errors::RecoveryError;
+/// use bytewax::py_wrap_error;
+/// use pyo3::Python;
+///
+/// #[derive(Debug)]
+/// struct StoreCorrupt(String);
+///
+/// impl std::fmt::Display for StoreCorrupt {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "recovery store corrupt: {}", self.0)
+///     }
+/// }
+/// impl std::error::Error for StoreCorrupt {}
+///
+/// py_wrap_error!(StoreCorrupt, RecoveryError);
+///
+/// fn load() -> Result<(), StoreCorrupt> {
+///     Err(StoreCorrupt("bad epoch".into()))
+/// }
+///
+/// fn load_py() -> pyo3::PyResult<()> {
+///     Ok(load()?)
+/// }
+///
+/// Python::with_gil(|py| {
+///     let err = load_py().unwrap_err();
+///     assert!(err.is_instance_of::<RecoveryError>(py));
+/// });
+/// ```
+macro_rules! py_wrap_error {
+    ($rust_err:ty, $py_exc:ty) => {
+        impl std::convert::From<$rust_err> for pyo3::PyErr {
+            fn from(err: $rust_err) -> pyo3::PyErr {
+                $crate::errors::wrap_error::<$py_exc>(&err)
+            }
+        }
+    };
+}
+
+/// Register [`BytewaxError`] and its subclasses on the `bytewax` module so
+/// `except bytewax.BytewaxError` (or a specific subclass) works from
+/// Python.
+pub(crate) fn register(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("BytewaxError", py.get_type::<BytewaxError>())?;
+    m.add("DataflowError", py.get_type::<DataflowError>())?;
+    m.add("RecoveryError", py.get_type::<RecoveryError>())?;
+    m.add("WindowConfigError", py.get_type::<WindowConfigError>())?;
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! Deterministic fault injection for exercising the recovery
+//! subsystem.
+//!
+//! [`ChaosConfig`] is threaded through [`super::build_production_dataflow`]
+//! and, when set, wraps the state-pair stream of every stateful step
+//! with a [`ChaosInjector`] that deterministically injects one of
+//! three faults at a chosen epoch: a worker panic, a bounded random
+//! delay before the step's progress/state write, or a dropped state
+//! write. The draw for a given epoch (per fault kind) is a hash of
+//! `(seed, worker_index, step_id, epoch, kind)` rather than the next
+//! value off a running RNG, so it only depends on the logical position
+//! in the dataflow: a worker restarted from a persisted `ResumeFrom`
+//! re-derives the exact same answer for every epoch it resumes
+//! through, whether or not it already faulted there before, so a test
+//! harness can assert output equivalence against a no-fault baseline
+//! run without risking a livelock on the epoch that originally
+//! faulted.
+//!
+//! All three faults are injected at the same point: the state-pair
+//! stream feeding into a stateful step's `stateful_unary`/
+//! `stateful_window_unary`, upstream of the `changes` it hands to the
+//! recovery writers. There's no hook here into
+//! `recovery::store`'s actual write call, so "dropped state write" is
+//! approximated by dropping the input record that would have produced
+//! one (the step never sees it, so it never computes or emits a
+//! change for that epoch) rather than by discarding an already-built
+//! `Change` after the fact. Reordering a write (as opposed to losing
+//! it outright) isn't injected here: Timely's frontier tracking ties a
+//! record's timestamp to the batch it arrives in, so reordering across
+//! batches safely needs a hook inside the recovery writers themselves,
+//! not this stream-level wrapper.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::dataflow::StepId;
+use crate::execution::WorkerIndex;
+
+/// Configuration for the opt-in chaos/fault-injection mode.
+///
+/// `seed` plus a step's `step_id` and the current worker's index
+/// together determine the exact sequence of injected faults, so a run
+/// is fully reproducible. `panic_probability`, `delay_probability`,
+/// and `drop_probability` are independent per-epoch draws (a single
+/// epoch could in principle draw more than one fault); any left at
+/// `0.0` (the default for everything but `panic_probability`, to keep
+/// existing callers working unchanged) never fires.
+#[pyclass(module = "bytewax.execution")]
+#[derive(Clone)]
+pub(crate) struct ChaosConfig {
+    seed: u64,
+    panic_probability: f64,
+    delay_probability: f64,
+    max_delay: Duration,
+    drop_probability: f64,
+}
+
+#[pymethods]
+impl ChaosConfig {
+    #[new]
+    #[pyo3(signature = (seed, panic_probability, delay_probability=0.0, max_delay=1.0, drop_probability=0.0))]
+    fn new(
+        seed: u64,
+        panic_probability: f64,
+        delay_probability: f64,
+        max_delay: f64,
+        drop_probability: f64,
+    ) -> Self {
+        Self {
+            seed,
+            panic_probability,
+            delay_probability,
+            max_delay: Duration::from_secs_f64(max_delay),
+            drop_probability,
+        }
+    }
+}
+
+impl ChaosConfig {
+    /// Build the injector a single step on a single worker should use.
+    ///
+    /// Each `(step_id, worker_index)` pair gets its own seed mixed
+    /// from `self.seed`, so faults in one step don't perturb the draws
+    /// another step would make; [`ChaosInjector`]'s `maybe_*` methods
+    /// mix the epoch (and fault kind) into this seed fresh on every
+    /// call, rather than pulling the next value off a running RNG.
+    pub(crate) fn injector_for(&self, worker_index: WorkerIndex, step_id: &StepId) -> ChaosInjector {
+        let mut seeder = StdRng::seed_from_u64(self.seed);
+        // Mix in the worker index and step id so each gets an
+        // independent, but still seed-deterministic, draw sequence.
+        let step_seed =
+            seeder.gen::<u64>() ^ (worker_index.0 as u64) ^ fnv1a(&format!("{:?}", step_id));
+        ChaosInjector {
+            step_seed,
+            panic_probability: self.panic_probability,
+            delay_probability: self.delay_probability,
+            max_delay: self.max_delay,
+            drop_probability: self.drop_probability,
+            step_id: step_id.clone(),
+        }
+    }
+}
+
+/// A tiny, dependency-free FNV-1a hash, just to mix a `StepId` into a
+/// `u64` seed without pulling in a hashing crate for this alone.
+fn fnv1a(s: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in s.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The pure, `StepId`-free half of [`ChaosInjector::draw`]: a draw in
+/// `[0, 1)` that's a function of `(step_seed, epoch, kind)` alone, not
+/// the next value off a persistent RNG. Split out so the epoch-keyed
+/// determinism the whole fault-injection feature depends on is
+/// testable on its own.
+fn draw_for_seed(step_seed: u64, epoch: u64, kind: &str) -> f64 {
+    let epoch_seed = step_seed ^ fnv1a(&format!("{epoch}:{kind}"));
+    StdRng::seed_from_u64(epoch_seed).gen::<f64>()
+}
+
+#[test]
+fn draw_for_seed_is_deterministic_across_calls() {
+    // Simulates re-deriving the same answer after a chaos-triggered
+    // restart: a fresh call with the same inputs must draw the same
+    // value, not the next value off a running RNG.
+    assert_eq!(draw_for_seed(42, 7, "panic"), draw_for_seed(42, 7, "panic"));
+}
+
+#[test]
+fn draw_for_seed_is_independent_per_kind() {
+    let panic_draw = draw_for_seed(42, 7, "panic");
+    let delay_draw = draw_for_seed(42, 7, "delay");
+    let drop_draw = draw_for_seed(42, 7, "drop");
+    assert_ne!(panic_draw, delay_draw);
+    assert_ne!(panic_draw, drop_draw);
+    assert_ne!(delay_draw, drop_draw);
+}
+
+#[test]
+fn draw_for_seed_varies_by_epoch() {
+    let draws: Vec<f64> = (0..5).map(|epoch| draw_for_seed(42, epoch, "panic")).collect();
+    for i in 1..draws.len() {
+        assert_ne!(draws[i - 1], draws[i]);
+    }
+}
+
+/// Per-step, per-worker fault injector.
+///
+/// Draws are keyed on `(epoch, fault kind)`, not on wall-clock time or
+/// call order, so the same logical position in the dataflow always
+/// gets the same answer to "does this epoch fault, and how?", no
+/// matter how many times it's been asked before (e.g. across a
+/// chaos-triggered restart).
+#[derive(Clone)]
+pub(crate) struct ChaosInjector {
+    step_seed: u64,
+    panic_probability: f64,
+    delay_probability: f64,
+    max_delay: Duration,
+    drop_probability: f64,
+    step_id: StepId,
+}
+
+/// Panic payload used by [`ChaosInjector::maybe_panic`] so a test
+/// harness can tell an injected fault apart from a genuine bug.
+#[derive(Debug)]
+pub(crate) struct InjectedFault {
+    pub(crate) step_id: StepId,
+    pub(crate) epoch: u64,
+}
+
+impl ChaosInjector {
+    /// A draw in `[0, 1)`, a pure function of `(step_seed, epoch,
+    /// kind)` rather than the next value off a persistent RNG, so the
+    /// same `(epoch, kind)` always draws the same value regardless of
+    /// how many times (or from how many restarts) it's been asked
+    /// before. Without that, a fresh `StdRng` reseeded on every worker
+    /// restart (see `ChaosConfig::injector_for`) would repeat its very
+    /// first draw every time, so a fault on the first epoch processed
+    /// after any restart would fire forever instead of letting the
+    /// worker make forward progress. `kind` keeps the three fault
+    /// kinds' draws independent of each other, so (for example) a high
+    /// `drop_probability` doesn't skew which epochs also panic.
+    fn draw(&self, epoch: u64, kind: &str) -> f64 {
+        draw_for_seed(self.step_seed, epoch, kind)
+    }
+
+    /// Possibly panic with a [`InjectedFault`] tagged with `epoch`.
+    ///
+    /// Called once per record that flows through the wrapped step;
+    /// since the draw depends only on `epoch`, the first record of a
+    /// faulting epoch is the one that panics.
+    pub(crate) fn maybe_panic(&self, epoch: u64) {
+        if self.draw(epoch, "panic") < self.panic_probability {
+            std::panic::panic_any(InjectedFault {
+                step_id: self.step_id.clone(),
+                epoch,
+            });
+        }
+    }
+
+    /// Possibly block the calling thread for a bounded random delay
+    /// before this epoch's progress/state write, to exercise recovery
+    /// paths that assume a write can be slow (but not lost) relative
+    /// to other workers' epochs.
+    ///
+    /// The delay is itself a deterministic fraction of `max_delay`,
+    /// drawn the same way as [`Self::maybe_panic`]'s fault draw, so a
+    /// re-run with the same seed sleeps for the same duration.
+    pub(crate) fn maybe_delay(&self, epoch: u64) {
+        if self.draw(epoch, "delay") < self.delay_probability {
+            let frac = self.draw(epoch, "delay_amount");
+            std::thread::sleep(self.max_delay.mul_f64(frac));
+        }
+    }
+
+    /// Whether this epoch's state write should be dropped, i.e. the
+    /// record that would have produced it never reaches the step.
+    pub(crate) fn should_drop(&self, epoch: u64) -> bool {
+        self.draw(epoch, "drop") < self.drop_probability
+    }
+}
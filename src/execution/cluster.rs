@@ -0,0 +1,205 @@
+//! Dynamic cluster rendezvous, so `cluster_main` doesn't need the full
+//! peer address list computed up front.
+//!
+//! [`ClusterConfig`] is an alternative to passing `cluster_main` a
+//! static `addresses` list: each process `PUT`s its own `host:port`
+//! under a shared `run_id` at a small HTTP key/value rendezvous
+//! endpoint, then polls the same endpoint until `process_count` peers
+//! have checked in, and materializes the full, agreed-upon address
+//! list (this process's position within it becomes its `proc_id`).
+//! This mirrors the worker-registers-with-scheduler model Dask's
+//! distributed worker/client uses, without pulling in a full
+//! coordination-service client for it — any HTTP KV store that
+//! supports a `PUT key/value` and a `GET prefix -> newline-separated
+//! values` (an etcd v3 HTTP gateway, a tiny sidecar, ...) works as
+//! `rendezvous_url`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::errors::{tracked_err, PythonException};
+
+/// Configuration for dynamic rendezvous instead of a static address
+/// list.
+///
+/// Pass to `cluster_main` alongside `self_addr` (this process's own
+/// `host:port`, which it registers) instead of `addresses`/`proc_id`;
+/// lets N identical processes (e.g. pods in a Kubernetes `Deployment`)
+/// find each other without pre-computing every address.
+#[pyclass(module = "bytewax.execution")]
+#[derive(Clone)]
+pub(crate) struct ClusterConfig {
+    rendezvous_url: String,
+    run_id: String,
+    process_count: usize,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+#[pymethods]
+impl ClusterConfig {
+    #[new]
+    #[pyo3(
+        text_signature = "(rendezvous_url, run_id, process_count, poll_interval=0.5, timeout=60.0)"
+    )]
+    fn new(
+        rendezvous_url: String,
+        run_id: String,
+        process_count: usize,
+        poll_interval: Option<f64>,
+        timeout: Option<f64>,
+    ) -> Self {
+        Self {
+            rendezvous_url,
+            run_id,
+            process_count,
+            poll_interval: Duration::from_secs_f64(poll_interval.unwrap_or(0.5)),
+            timeout: Duration::from_secs_f64(timeout.unwrap_or(60.0)),
+        }
+    }
+}
+
+impl ClusterConfig {
+    /// Register `self_addr` under this run, then block until all
+    /// `process_count` peers have checked in.
+    ///
+    /// Returns the full address list in sorted order (stable across
+    /// every process, since it's sorted rather than taken in arrival
+    /// order at this process) and this process's index within it, to
+    /// use as `proc_id`.
+    pub(crate) fn rendezvous(&self, self_addr: &str) -> PyResult<(Vec<String>, usize)> {
+        self.register(self_addr)?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let mut peers = self.list_registered()?;
+            if peers.len() >= self.process_count {
+                // More than `process_count` registrations can pile up
+                // under the same `run_id` (a re-registering retried
+                // process, a reused `run_id`, ...). Truncate to the
+                // first `process_count` sorted entries so every
+                // process polling at a slightly different moment
+                // converges on the same address list; otherwise two
+                // processes could disagree on each other's index,
+                // which `timely::CommunicationConfig::Cluster`
+                // requires to match exactly.
+                peers.truncate(self.process_count);
+                let proc_id = peers.iter().position(|addr| addr == self_addr).ok_or_else(|| {
+                    tracked_err::<PyRuntimeError>(
+                        "registered address went missing from the rendezvous endpoint",
+                    )
+                })?;
+                return Ok((peers, proc_id));
+            }
+            if Instant::now() >= deadline {
+                return Err(tracked_err::<PyRuntimeError>(&format!(
+                    "timed out after {:?} waiting for {} peers to rendezvous at {} \
+                    (run {}); only {} registered so far",
+                    self.timeout,
+                    self.process_count,
+                    self.rendezvous_url,
+                    self.run_id,
+                    peers.len(),
+                )));
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    fn register(&self, self_addr: &str) -> PyResult<()> {
+        ureq::put(&format!(
+            "{}/{}/{}",
+            self.rendezvous_url, self.run_id, self_addr
+        ))
+        .call()
+        .map(|_| ())
+        .raise::<PyRuntimeError>("error registering this process with the rendezvous endpoint")
+    }
+
+    /// List every `host:port` registered so far under `run_id`,
+    /// deduplicated and sorted so every process derives the same
+    /// `proc_id` assignment from it.
+    ///
+    /// A retried registration (or any other reason the same address
+    /// ends up `PUT` more than once) must not occupy two slots once
+    /// [`Self::rendezvous`] truncates this to `process_count` entries
+    /// -- that would either wedge rendezvous below `process_count`
+    /// forever or evict a genuinely distinct peer -- so dedup right
+    /// after the sort, which is free and doesn't depend on whatever
+    /// the backend does with repeated `PUT`s of the same address.
+    fn list_registered(&self) -> PyResult<Vec<String>> {
+        let body = ureq::get(&format!("{}/{}", self.rendezvous_url, self.run_id))
+            .call()
+            .raise::<PyRuntimeError>("error listing peers at the rendezvous endpoint")?
+            .into_string()
+            .raise::<PyRuntimeError>("error reading the rendezvous endpoint's response")?;
+        Ok(parse_peer_list(&body))
+    }
+}
+
+/// Pull the registered `host:port` entries out of a rendezvous
+/// endpoint response body (one per line), deduplicated and sorted.
+///
+/// Split out of [`ClusterConfig::list_registered`] so this parsing --
+/// the part that actually determines what `rendezvous` truncates to
+/// `process_count` -- is testable without a live rendezvous endpoint.
+fn parse_peer_list(body: &str) -> Vec<String> {
+    let mut peers: Vec<String> =
+        body.lines().map(str::to_string).filter(|line| !line.is_empty()).collect();
+    peers.sort();
+    peers.dedup();
+    peers
+}
+
+#[test]
+fn parse_peer_list_dedups_and_sorts() {
+    let found = parse_peer_list("b:1\na:1\nb:1\n\nc:1\n");
+    let expected = vec!["a:1".to_string(), "b:1".to_string(), "c:1".to_string()];
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn truncate_after_dedup_keeps_every_distinct_peer() {
+    // A retried registration of "a:1" must not cost "b:1" its slot
+    // once `rendezvous` truncates to `process_count` (2 here): without
+    // the `dedup()` in `parse_peer_list`, the sorted list would be
+    // ["a:1", "a:1", "b:1"] and truncating to 2 would keep two copies
+    // of "a:1" and silently drop the genuinely distinct "b:1".
+    let mut peers = parse_peer_list("a:1\na:1\nb:1\n");
+    peers.truncate(2);
+    assert_eq!(peers, vec!["a:1".to_string(), "b:1".to_string()]);
+}
+
+/// Resolve `addresses`/`proc_id` for `cluster_main`, either from the
+/// caller-supplied values or, if `cluster_config` is set, by blocking
+/// on rendezvous with `self_addr`.
+pub(crate) fn resolve_addresses(
+    addresses: Option<Vec<String>>,
+    proc_id: Option<usize>,
+    cluster_config: Option<ClusterConfig>,
+    self_addr: Option<String>,
+) -> PyResult<(Option<Vec<String>>, usize)> {
+    match cluster_config {
+        None => {
+            let proc_id = proc_id.ok_or_else(|| {
+                tracked_err::<PyValueError>("'proc_id' is required unless 'cluster_config' is set")
+            })?;
+            Ok((addresses, proc_id))
+        }
+        Some(cluster_config) => {
+            if addresses.is_some() || proc_id.is_some() {
+                return Err(tracked_err::<PyValueError>(
+                    "can't specify 'addresses'/'proc_id' together with 'cluster_config'",
+                ));
+            }
+            let self_addr = self_addr.ok_or_else(|| {
+                tracked_err::<PyValueError>("'self_addr' is required when 'cluster_config' is set")
+            })?;
+            let (addresses, proc_id) = cluster_config.rendezvous(&self_addr)?;
+            Ok((Some(addresses), proc_id))
+        }
+    }
+}
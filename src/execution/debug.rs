@@ -0,0 +1,144 @@
+//! Cross-worker interactive debug mode for operator exceptions.
+//!
+//! [`DebugConfig`] is threaded through [`super::build_production_dataflow`]
+//! and, when set, wraps the per-step closures that call into user code
+//! (`Map`, `FlatMap`, `Filter`, `FilterMap`) with [`DebugConfig::guard`].
+//! Instead of letting an exception escaping one of those closures
+//! propagate straight to a panic (and eventually a reraised traceback
+//! after the whole worker tears down, same as today), the faulting
+//! worker captures the step kind, epoch, worker index, and a `repr()`
+//! of the offending item, then drops into a post-mortem hook
+//! (`hook`, defaulting to `pdb.post_mortem`) while holding the GIL.
+//!
+//! Only one worker may be inside the hook at a time: [`DEBUG_GATE`] is
+//! a process-wide lock that the others park on rather than racing for
+//! stdin. Parking uses a polling [`Mutex::try_lock`] loop instead of a
+//! blocking [`Mutex::lock`] so a parked worker can still notice
+//! `should_abort` (the same interrupt flag `Driver::stop()` sets) and
+//! give up instead of waiting on a debugger session that's never
+//! coming.
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use pyo3::prelude::*;
+
+use crate::execution::WorkerIndex;
+
+static DEBUG_GATE: Mutex<()> = Mutex::new(());
+
+/// Configuration for the opt-in interactive debug mode.
+///
+/// Pass to `run_main`/`cluster_main` as `debug_mode`. If `hook` is
+/// `None`, a faulting worker calls `pdb.post_mortem()` (reading the
+/// exception this sets as "current" via `PyErr::restore`); pass your
+/// own zero-argument callable to use a different debugger (e.g.
+/// `ipdb.post_mortem` or a remote debug adapter).
+#[pyclass(module = "bytewax.execution")]
+#[derive(Clone)]
+pub(crate) struct DebugConfig {
+    hook: Option<Py<PyAny>>,
+}
+
+#[pymethods]
+impl DebugConfig {
+    #[new]
+    #[pyo3(text_signature = "(hook=None)")]
+    fn new(hook: Option<Py<PyAny>>) -> Self {
+        Self { hook }
+    }
+}
+
+/// Everything we know about a fault, for the message printed before
+/// the post-mortem hook runs.
+///
+/// `step_label` is the step's kind (`"Map"`, `"Filter"`, ...) rather
+/// than a `StepId`: the steps this module guards don't carry one.
+pub(crate) struct FaultContext {
+    pub(crate) step_label: String,
+    pub(crate) worker_index: WorkerIndex,
+    pub(crate) epoch: u64,
+    pub(crate) item_repr: String,
+}
+
+impl DebugConfig {
+    /// Run `f`. If it panics with a `PyErr` payload, pause on
+    /// [`DEBUG_GATE`] to drop into the post-mortem hook before
+    /// re-raising the same panic, instead of letting it propagate
+    /// immediately.
+    ///
+    /// `ctx` is only evaluated on the faulting path (it usually builds
+    /// a `repr()` of the item, which isn't free), and `should_abort` is
+    /// the same interrupt flag `Driver::stop()` sets, so a debug
+    /// session in progress on another worker doesn't block a
+    /// requested shutdown forever.
+    ///
+    /// Behaves exactly like calling `f()` directly when this
+    /// `DebugConfig`'s hook never runs (the `Ok` path below), so
+    /// callers that don't wire up `debug_mode` see no change; existing
+    /// panic -> `PyErr` unwinding (see [`super::unwrap_worker_panic`])
+    /// still applies once this returns/re-panics.
+    pub(crate) fn guard<D>(
+        &self,
+        should_abort: &AtomicBool,
+        ctx: impl FnOnce() -> FaultContext,
+        f: impl FnOnce() -> D + panic::UnwindSafe,
+    ) -> D {
+        match panic::catch_unwind(f) {
+            Ok(value) => value,
+            Err(payload) => {
+                if let Some(err) = payload.downcast_ref::<PyErr>() {
+                    if !should_abort.load(Ordering::Relaxed) {
+                        self.post_mortem(should_abort, ctx(), err);
+                    }
+                }
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    fn post_mortem(&self, should_abort: &AtomicBool, ctx: FaultContext, err: &PyErr) {
+        // Wait our turn, but keep re-checking `should_abort` instead of
+        // blocking on `DEBUG_GATE::lock()` so a `Driver::stop()` fired
+        // while we're parked here still gets through.
+        let _gate = loop {
+            if should_abort.load(Ordering::Relaxed) {
+                return;
+            }
+            match DEBUG_GATE.try_lock() {
+                Ok(gate) => break gate,
+                Err(_) => std::thread::sleep(Duration::from_millis(25)),
+            }
+        };
+
+        Python::with_gil(|py| {
+            eprintln!(
+                "\nbytewax debug_mode: worker {} hit an exception in a {} step at epoch {}\n  item: {}\n",
+                ctx.worker_index.0, ctx.step_label, ctx.epoch, ctx.item_repr,
+            );
+
+            // Make `err` the "current" exception so a default
+            // `pdb.post_mortem()` (which reads `sys.exc_info()`) has
+            // something to inspect.
+            err.clone_ref(py).restore(py);
+
+            let hook_res = match &self.hook {
+                Some(hook) => hook.call0(py).map(|_| ()),
+                None => py
+                    .import("pdb")
+                    .and_then(|pdb| pdb.getattr("post_mortem"))
+                    .and_then(|post_mortem| post_mortem.call0())
+                    .map(|_| ()),
+            };
+            // `restore` leaves its error set on the interpreter; clear
+            // it so our own `catch_unwind`/`unwrap_any!` dance above
+            // re-raises the original `err`, not a stale one.
+            let _ = PyErr::take(py);
+            if let Err(hook_err) = hook_res {
+                eprintln!("bytewax debug_mode: post-mortem hook itself failed: {hook_err}");
+            }
+        });
+    }
+}
@@ -0,0 +1,155 @@
+//! Handles for driving a dataflow once it has started executing:
+//! [`Driver`] for a programmatic stop signal, and [`RunningFlow`] (the
+//! handle returned by `super::run_main_async`) for polling a
+//! background dataflow to completion.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+use crate::errors::tracked_err;
+use crate::execution::unwrap_worker_panic;
+
+/// A handle that can stop a running dataflow from outside the thread
+/// that is executing it.
+///
+/// Create one and pass it to `run_main`/`cluster_main`/
+/// `run_main_async` as the `driver` argument, then call `stop()` from a
+/// signal handler, a webserver endpoint, or a supervising coroutine to
+/// shut the dataflow down cleanly instead of relying on
+/// `KeyboardInterrupt`.
+#[pyclass(module = "bytewax.execution")]
+#[derive(Clone)]
+pub(crate) struct Driver {
+    interrupt_flag: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl Driver {
+    #[new]
+    #[pyo3(text_signature = "()")]
+    fn new() -> Self {
+        Self {
+            interrupt_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Signal the dataflow this `Driver` is attached to to stop at the
+    /// next epoch boundary.
+    ///
+    /// This flips the same `interrupt_flag` the `WorkerRunner` checks
+    /// each step, so the worker winds down cleanly (flushing any
+    /// pending recovery writes through `attach_recovery_to_dataflow`)
+    /// rather than being killed outright.
+    fn stop(&self) {
+        self.interrupt_flag.store(true, Ordering::Release);
+    }
+}
+
+impl Driver {
+    /// A fresh, not-yet-stopped driver, for call sites that don't need
+    /// to expose one to Python (e.g. `run_main`, which blocks and so
+    /// has no handle to hand out unless the caller supplied a
+    /// `Driver`).
+    pub(crate) fn unattached() -> Self {
+        Self::new()
+    }
+
+    pub(crate) fn interrupt_flag(&self) -> &Arc<AtomicBool> {
+        &self.interrupt_flag
+    }
+}
+
+/// A running dataflow started by `run_main_async`.
+///
+/// Holds the background worker's `JoinHandle` and the [`Driver`] it
+/// shares with the `WorkerRunner`, so the same object can be used both
+/// to poll for completion and to ask the dataflow to stop.
+#[pyclass(module = "bytewax.execution")]
+pub(crate) struct RunningFlow {
+    join_handle: Option<JoinHandle<std::thread::Result<()>>>,
+    driver: Driver,
+}
+
+impl RunningFlow {
+    pub(crate) fn new(join_handle: JoinHandle<std::thread::Result<()>>, driver: Driver) -> Self {
+        Self {
+            join_handle: Some(join_handle),
+            driver,
+        }
+    }
+}
+
+#[pymethods]
+impl RunningFlow {
+    /// Poll the dataflow for completion.
+    ///
+    /// Returns `None` while the dataflow is still running. Once it has
+    /// finished, returns `None` cleanly if it succeeded, or re-raises
+    /// the `PyErr` the worker panicked with (the same exception
+    /// `run_main` would have raised had it been called synchronously).
+    ///
+    /// Calling this again after the dataflow has finished is a no-op
+    /// that returns `None` immediately.
+    fn await_result(&mut self, py: Python) -> PyResult<()> {
+        let is_finished = match &self.join_handle {
+            Some(join_handle) => join_handle.is_finished(),
+            None => return Ok(()),
+        };
+        if !is_finished {
+            return Ok(());
+        }
+
+        // Unwraps ok: we just checked `is_finished` above under the
+        // `Some` branch.
+        let join_handle = self.join_handle.take().unwrap();
+        let panic_res = py
+            .allow_threads(|| join_handle.join())
+            .map_err(|_| tracked_err::<PyRuntimeError>("dataflow worker thread panicked"))?;
+
+        panic_res.map_err(|panic_err| unwrap_worker_panic(py, panic_err))
+    }
+
+    /// Block until the dataflow finishes, then re-raise any error it
+    /// encountered, same as `await_result`.
+    ///
+    /// `timeout`, in seconds, bounds how long this waits; if it elapses
+    /// before the dataflow finishes, returns `None` without consuming
+    /// the result, so a later call (with or without a timeout) can
+    /// still pick it up. With no `timeout` (the default), waits
+    /// indefinitely, same as the plain blocking `cluster_main`/
+    /// `run_main` this handle's `_async` counterpart replaces.
+    #[pyo3(signature = (timeout=None))]
+    fn join(&mut self, py: Python, timeout: Option<f64>) -> PyResult<()> {
+        let deadline = timeout.map(|secs| Instant::now() + Duration::from_secs_f64(secs));
+        loop {
+            if self.is_finished() {
+                return self.await_result(py);
+            }
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    return Ok(());
+                }
+            }
+            py.allow_threads(|| std::thread::sleep(Duration::from_millis(10)));
+        }
+    }
+
+    /// Ask the dataflow to stop at the next epoch boundary. Equivalent
+    /// to calling `stop()` on the `Driver` this handle was built with.
+    fn stop(&self) {
+        self.driver.stop();
+    }
+
+    /// Whether the background worker thread has finished running.
+    fn is_finished(&self) -> bool {
+        self.join_handle
+            .as_ref()
+            .map(|join_handle| join_handle.is_finished())
+            .unwrap_or(true)
+    }
+}
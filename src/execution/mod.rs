@@ -14,13 +14,23 @@
 //! See [`crate::recovery`] for a description of the recovery
 //! components added to the Timely dataflow.
 
+mod chaos;
+mod cluster;
+mod debug;
+mod handle;
+mod otel;
 mod runner;
 
 use timely::dataflow::operators::{Concatenate, Filter, Inspect, Map, ResultStream, ToStream};
 use tokio::runtime::Runtime;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::dataflow::{Dataflow, Step};
 use crate::errors::{prepend_tname, tracked_err, PythonException};
+use crate::execution::chaos::ChaosConfig;
+use crate::execution::cluster::ClusterConfig;
+use crate::execution::debug::DebugConfig;
+use crate::execution::handle::{Driver, RunningFlow};
 use crate::execution::runner::WorkerRunner;
 use crate::inputs::{DynamicInput, EpochInterval, PartitionedInput};
 use crate::operators::collect_window::CollectWindowLogic;
@@ -50,9 +60,13 @@ use pyo3::exceptions::{PyKeyboardInterrupt, PyRuntimeError, PyTypeError, PyValue
 use pyo3::prelude::*;
 use pyo3::types::PyType;
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::process::Command;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -103,6 +117,22 @@ fn worker_count_iter_works() {
 /// concepts to Timely, as we are using Timely as a basis to implement
 /// more-complicated Bytewax features like input builders and
 /// recovery.
+///
+/// `chaos_config`, when set, wraps the state-pair stream of every
+/// stateful step with a [`chaos::ChaosInjector`] so the opt-in
+/// fault-injection mode (see [`chaos`]) can deterministically panic,
+/// delay, or drop a chosen step's epoch to exercise the recovery
+/// subsystem's different failure paths.
+///
+/// `debug_config`, when set, wraps the `Map`/`FlatMap`/`Filter`/
+/// `FilterMap` closures (the ones that call straight into user code
+/// without going through a stateful operator) with
+/// [`debug::DebugConfig::guard`], so an exception one of them raises
+/// drops the faulting worker into a post-mortem hook instead of
+/// propagating immediately. Stateful and windowed steps run their
+/// user closures inside `crate::operators::{stateful_unary,
+/// stateful_window_unary}`, outside of what this function builds
+/// directly, so they aren't wrapped here.
 #[allow(clippy::too_many_arguments)]
 fn build_production_dataflow<A, PW, SW>(
     py: Python,
@@ -115,6 +145,9 @@ fn build_production_dataflow<A, PW, SW>(
     store_summary: StoreSummary,
     mut progress_writer: PW,
     state_writer: SW,
+    chaos_config: Option<ChaosConfig>,
+    debug_config: Option<DebugConfig>,
+    interrupt_flag: Arc<AtomicBool>,
 ) -> PyResult<ProbeHandle<u64>>
 where
     A: Allocate,
@@ -125,6 +158,7 @@ where
 
     let worker_index = WorkerIndex(worker.index());
     let worker_count = WorkerCount(worker.peers());
+    crate::logging::set_current_worker(worker_index.0);
 
     let worker_key = WorkerKey(ex, worker_index);
 
@@ -172,6 +206,7 @@ where
                         .build(py)
                         .reraise("error building CollectWindow windower")?;
 
+                    let stream = maybe_inject_chaos(&chaos_config, worker_index, &step_id, stream);
                     let (output, changes) = stream.map(extract_state_pair).stateful_window_unary(
                         step_id,
                         clock_builder,
@@ -182,11 +217,7 @@ where
                     );
 
                     stream = output
-                        .map(|(key, result)| {
-                            result
-                                .map(|value| (key.clone(), value))
-                                .map_err(|err| (key.clone(), err))
-                        })
+                        .map(|(key, result)| repack_window_result(key, result))
                         // For now, filter to just reductions and
                         // ignore late values.
                         .ok()
@@ -235,17 +266,73 @@ where
                     }
                 }
                 Step::Map { mapper } => {
-                    stream = stream.map(move |item| map(&mapper, item));
+                    let debug_config = debug_config.clone();
+                    let interrupt_flag = Arc::clone(&interrupt_flag);
+                    let (tapped, epoch_cell) = maybe_tap_epoch(stream, &debug_config);
+                    stream = tapped.map(move |item| {
+                        let epoch = epoch_cell.as_ref().map_or(0, |c| c.get());
+                        maybe_debug_step(
+                            &debug_config,
+                            &interrupt_flag,
+                            worker_index,
+                            "Map",
+                            epoch,
+                            item,
+                            move |item| map(&mapper, item),
+                        )
+                    });
                 }
                 Step::FlatMap { mapper } => {
-                    stream = stream.flat_map(move |item| flat_map(&mapper, item));
+                    let debug_config = debug_config.clone();
+                    let interrupt_flag = Arc::clone(&interrupt_flag);
+                    let (tapped, epoch_cell) = maybe_tap_epoch(stream, &debug_config);
+                    stream = tapped.flat_map(move |item| {
+                        let epoch = epoch_cell.as_ref().map_or(0, |c| c.get());
+                        maybe_debug_step(
+                            &debug_config,
+                            &interrupt_flag,
+                            worker_index,
+                            "FlatMap",
+                            epoch,
+                            item,
+                            move |item| flat_map(&mapper, item),
+                        )
+                    });
                 }
                 Step::Filter { predicate } => {
-                    stream = stream.filter(move |item| filter(&predicate, item));
+                    let debug_config = debug_config.clone();
+                    let interrupt_flag = Arc::clone(&interrupt_flag);
+                    let (tapped, epoch_cell) = maybe_tap_epoch(stream, &debug_config);
+                    stream = tapped.filter(move |item| {
+                        let epoch = epoch_cell.as_ref().map_or(0, |c| c.get());
+                        maybe_debug_step(
+                            &debug_config,
+                            &interrupt_flag,
+                            worker_index,
+                            "Filter",
+                            epoch,
+                            item,
+                            move |item| filter(&predicate, item),
+                        )
+                    });
                 }
                 Step::FilterMap { mapper } => {
-                    stream = stream
-                        .map(move |item| map(&mapper, item))
+                    let debug_config = debug_config.clone();
+                    let interrupt_flag = Arc::clone(&interrupt_flag);
+                    let (tapped, epoch_cell) = maybe_tap_epoch(stream, &debug_config);
+                    stream = tapped
+                        .map(move |item| {
+                            let epoch = epoch_cell.as_ref().map_or(0, |c| c.get());
+                            maybe_debug_step(
+                                &debug_config,
+                                &interrupt_flag,
+                                worker_index,
+                                "FilterMap",
+                                epoch,
+                                item,
+                                move |item| map(&mapper, item),
+                            )
+                        })
                         .filter(move |item| Python::with_gil(|py| !item.is_none(py)));
                 }
                 Step::FoldWindow {
@@ -264,6 +351,7 @@ where
                         .build(py)
                         .reraise("error building FoldWindow windower")?;
 
+                    let stream = maybe_inject_chaos(&chaos_config, worker_index, &step_id, stream);
                     let (output, changes) = stream.map(extract_state_pair).stateful_window_unary(
                         step_id,
                         clock_builder,
@@ -274,11 +362,7 @@ where
                     );
 
                     stream = output
-                        .map(|(key, result)| {
-                            result
-                                .map(|value| (key.clone(), value))
-                                .map_err(|err| (key.clone(), err))
-                        })
+                        .map(|(key, result)| repack_window_result(key, result))
                         // For now, filter to just reductions and
                         // ignore late values.
                         .ok()
@@ -299,6 +383,7 @@ where
                 } => {
                     let step_resume_state = resume_state.remove(&step_id);
 
+                    let stream = maybe_inject_chaos(&chaos_config, worker_index, &step_id, stream);
                     let (output, changes) = stream.map(extract_state_pair).stateful_unary(
                         step_id,
                         ReduceLogic::builder(reducer, is_complete),
@@ -323,6 +408,7 @@ where
                         .build(py)
                         .reraise("error building ReduceWindow windower")?;
 
+                    let stream = maybe_inject_chaos(&chaos_config, worker_index, &step_id, stream);
                     let (output, changes) = stream.map(extract_state_pair).stateful_window_unary(
                         step_id,
                         clock_builder,
@@ -333,11 +419,7 @@ where
                     );
 
                     stream = output
-                        .map(|(key, result)| {
-                            result
-                                .map(|value| (key.clone(), value))
-                                .map_err(|err| (key.clone(), err))
-                        })
+                        .map(|(key, result)| repack_window_result(key, result))
                         // For now, filter to just reductions and
                         // ignore late values.
                         .ok()
@@ -351,6 +433,7 @@ where
                 } => {
                     let step_resume_state = resume_state.remove(&step_id);
 
+                    let stream = maybe_inject_chaos(&chaos_config, worker_index, &step_id, stream);
                     let (output, changes) = stream.map(extract_state_pair).stateful_unary(
                         step_id,
                         StatefulMapLogic::builder(builder, mapper),
@@ -429,6 +512,141 @@ where
     })
 }
 
+/// Wrap `stream` so that, when `chaos_config` is set, the step
+/// identified by `step_id` deterministically panics, sleeps, or drops
+/// records on some epochs (see [`chaos::ChaosInjector`]).
+///
+/// A no-op (returns `stream` unchanged) when `chaos_config` is `None`,
+/// so this can be called unconditionally at every stateful step
+/// without changing behavior for the common case.
+fn maybe_inject_chaos<G, D>(
+    chaos_config: &Option<ChaosConfig>,
+    worker_index: WorkerIndex,
+    step_id: &crate::dataflow::StepId,
+    stream: timely::dataflow::Stream<G, D>,
+) -> timely::dataflow::Stream<G, D>
+where
+    G: timely::dataflow::Scope<Timestamp = u64>,
+    D: timely::Data,
+{
+    match chaos_config {
+        Some(chaos_config) => {
+            let injector = chaos_config.injector_for(worker_index, step_id);
+            let epoch_cell = Rc::new(Cell::new(0u64));
+            let tapped = tap_epoch(stream, &epoch_cell);
+            let panic_and_delay_injector = injector.clone();
+            // Only consider a delay on the first record of each epoch,
+            // so a delayed epoch sleeps once rather than once per record.
+            let last_delayed_epoch = Cell::new(None);
+            tapped
+                .inspect_time(move |epoch, _| {
+                    panic_and_delay_injector.maybe_panic(*epoch);
+                    if last_delayed_epoch.get() != Some(*epoch) {
+                        last_delayed_epoch.set(Some(*epoch));
+                        panic_and_delay_injector.maybe_delay(*epoch);
+                    }
+                })
+                .filter(move |_| !injector.should_drop(epoch_cell.get()))
+        }
+        None => stream,
+    }
+}
+
+/// Keep `epoch_cell` updated with the epoch of whatever record last
+/// passed through `stream`, so a closure chained immediately after
+/// (e.g. the `Map`/`Filter`/... closures [`maybe_debug_step`] guards)
+/// can report which epoch a fault happened in without `debug_mode`
+/// plumbing an epoch argument through every operator signature.
+///
+/// Also updates `crate::logging`'s current-epoch thread-local, so
+/// telemetry captured by `init_logging` while this step runs is
+/// tagged with the same epoch.
+fn tap_epoch<G, D>(stream: timely::dataflow::Stream<G, D>, epoch_cell: &Rc<Cell<u64>>) -> timely::dataflow::Stream<G, D>
+where
+    G: timely::dataflow::Scope<Timestamp = u64>,
+    D: timely::Data,
+{
+    let epoch_cell = Rc::clone(epoch_cell);
+    stream.inspect_time(move |epoch, _| {
+        epoch_cell.set(*epoch);
+        crate::logging::set_current_epoch(*epoch);
+    })
+}
+
+/// [`tap_epoch`] the stream, but only when `debug_mode` is actually
+/// enabled, to label [`maybe_debug_step`]'s fault message. A run with
+/// no `debug_config` skips the extra `inspect_time` operator, so this
+/// step doesn't update `crate::logging`'s current epoch either.
+fn maybe_tap_epoch<G, D>(
+    stream: timely::dataflow::Stream<G, D>,
+    debug_config: &Option<DebugConfig>,
+) -> (timely::dataflow::Stream<G, D>, Option<Rc<Cell<u64>>>)
+where
+    G: timely::dataflow::Scope<Timestamp = u64>,
+    D: timely::Data,
+{
+    match debug_config {
+        Some(_) => {
+            let epoch_cell = Rc::new(Cell::new(0u64));
+            (tap_epoch(stream, &epoch_cell), Some(epoch_cell))
+        }
+        None => (stream, None),
+    }
+}
+
+/// Run `body(item)`, guarding it with `debug_config`'s post-mortem
+/// hook (see [`debug::DebugConfig::guard`]) when `debug_mode` is
+/// enabled.
+///
+/// A no-op wrapper around `body(item)` when `debug_config` is `None`,
+/// so this can be called unconditionally at every stateless step
+/// without changing behavior for the common case: `item` is only
+/// cloned, and only formatted into the fault message, on the
+/// `Some(debug_config)` path, so a run with no `debug_config` pays
+/// neither cost.
+fn maybe_debug_step<D, I: Debug + Clone>(
+    debug_config: &Option<DebugConfig>,
+    interrupt_flag: &Arc<AtomicBool>,
+    worker_index: WorkerIndex,
+    step_label: &'static str,
+    epoch: u64,
+    item: I,
+    body: impl FnOnce(I) -> D + std::panic::UnwindSafe,
+) -> D {
+    match debug_config {
+        Some(debug_config) => {
+            let item_for_debug = item.clone();
+            debug_config.guard(
+                interrupt_flag,
+                || debug::FaultContext {
+                    step_label: step_label.to_string(),
+                    worker_index,
+                    epoch,
+                    item_repr: format!("{item_for_debug:?}"),
+                },
+                move || body(item),
+            )
+        }
+        None => body(item),
+    }
+}
+
+/// Re-pair a windowed step's `(key, Result<value, err>)` output back
+/// into `Result<(key, value), (key, err)>` so late/incomplete windows
+/// can be filtered with `.ok()` before `wrap_state_pair`.
+///
+/// Shared by the `CollectWindow`, `FoldWindow`, and `ReduceWindow`
+/// arms of [`build_production_dataflow`] above, which were each
+/// re-implementing this identically.
+fn repack_window_result<K, V, E>(key: K, result: Result<V, E>) -> Result<(K, V), (K, E)>
+where
+    K: Clone,
+{
+    result
+        .map(|value| (key.clone(), value))
+        .map_err(|err| (key.clone(), err))
+}
+
 // Struct used to handle a span that is closed and reopened periodically.
 struct PeriodicSpan {
     span: Option<EnteredSpan>,
@@ -456,6 +674,10 @@ impl PeriodicSpan {
             self.counter += 1;
             self.span = Some(tracing::trace_span!("Periodic", counter = self.counter).entered());
             self.last_open = Instant::now();
+            // A `TRACE` heartbeat, so `init_logging(callback,
+            // debug=True)` surfaces something for long-running epochs
+            // even when no other event fires within `self.length`.
+            tracing::trace!(counter = self.counter, "periodic heartbeat");
         }
     }
 }
@@ -526,26 +748,81 @@ fn is_in_bytewax_run(py: Python) -> PyResult<bool> {
 ///       `bytewax.recovery`. If `None`, state will not be
 ///       persisted.
 ///
-#[pyfunction(flow, "*", epoch_interval = "None", recovery_config = "None")]
-#[pyo3(text_signature = "(flow, *, epoch_interval, recovery_config)")]
+///   driver: A `Driver` to stop this dataflow from another thread
+///       (e.g. a signal handler or a webserver endpoint) by calling
+///       its `stop()` method. If `None`, a `Driver` is still created
+///       internally, but `run_main` has no way to hand it back to you
+///       since it blocks until the dataflow finishes; pass your own
+///       if you need to be able to stop it early.
+///
+///   chaos_config: Opt-in deterministic fault injection, for testing
+///       that the recovery subsystem resumes correctly. See
+///       `ChaosConfig`. If `None` (the default), no faults are
+///       injected.
+///
+///   debug_mode: Opt-in interactive debugger. When an exception
+///       escapes a `map`/`flat_map`/`filter`/`filter_map` step, drop
+///       into a post-mortem hook (`pdb.post_mortem` by default) on
+///       this worker instead of reraising right away. See
+///       `DebugConfig`. If `None` (the default), exceptions propagate
+///       as they do today.
+///
+/// Set the `BYTEWAX_OTEL_ENDPOINT` env var to export a per-epoch,
+/// per-worker OTLP trace to that collector for this run; unset, this
+/// has no effect on the plain `tracing::info!`-based logging above.
+#[pyfunction(
+    flow,
+    "*",
+    epoch_interval = "None",
+    recovery_config = "None",
+    driver = "None",
+    chaos_config = "None",
+    debug_mode = "None"
+)]
+#[pyo3(
+    text_signature = "(flow, *, epoch_interval, recovery_config, driver, chaos_config, debug_mode)"
+)]
 pub(crate) fn run_main(
     py: Python,
     flow: Py<Dataflow>,
     epoch_interval: Option<EpochInterval>,
     recovery_config: Option<Py<RecoveryConfig>>,
+    driver: Option<Driver>,
+    chaos_config: Option<ChaosConfig>,
+    debug_mode: Option<DebugConfig>,
 ) -> PyResult<()> {
     tracing::info!("Running single worker on single process");
+    let driver = driver.unwrap_or_else(Driver::unattached);
+    // `_otel_guard` just needs to outlive `py.allow_threads` below, so
+    // the exporter (if `BYTEWAX_OTEL_ENDPOINT` is set) keeps flushing
+    // spans for the whole blocking call. See `cluster_main`.
+    let _otel_guard = otel::init_from_env();
     let res = py.allow_threads(move || {
         std::panic::catch_unwind(|| {
             timely::execute::execute_directly::<(), _>(move |worker| {
-                let interrupt_flag = AtomicBool::new(false);
+                // Root span for this worker's whole run, same as
+                // `cluster_main`'s `spawn_cluster_workers`, so a
+                // `BYTEWAX_OTEL_ENDPOINT` trace groups the per-epoch
+                // spans `WorkerRunner::run` opens under one span
+                // instead of leaving them to float at the top level.
+                // `proc_id` is always 0 here: `run_main` only ever
+                // runs a single process.
+                let worker_index = WorkerIndex(worker.index());
+                let _root_span = tracing::info_span!(
+                    "bytewax_worker",
+                    proc_id = 0,
+                    worker_index = worker_index.0
+                )
+                .entered();
 
                 let worker_runner = WorkerRunner::new(
                     worker,
-                    &interrupt_flag,
+                    driver.interrupt_flag(),
                     flow,
                     epoch_interval.unwrap_or(EpochInterval::new(Duration::from_secs(10))),
                     recovery_config.unwrap_or(default_recovery_config()),
+                    chaos_config,
+                    debug_mode,
                 );
                 // The error will be reraised in the building phase.
                 // If an error occur during the execution, it will
@@ -558,35 +835,289 @@ pub(crate) fn run_main(
         })
     });
 
-    res.map_err(|panic_err| {
-        // The worker panicked.
-        // Print an empty line to separate rust panick message from the rest.
-        eprintln!("");
-        if let Some(err) = panic_err.downcast_ref::<PyErr>() {
-            // Special case for keyboard interrupt.
-            if err.get_type(py).is(PyType::new::<PyKeyboardInterrupt>(py)) {
-                tracked_err::<PyKeyboardInterrupt>(
-                    "interrupt signal received, all processes have been shut down",
-                )
-            } else {
-                // Panics with PyErr as payload should come from bytewax.
-                err.clone_ref(py)
-            }
-        } else if let Some(msg) = panic_err.downcast_ref::<String>() {
-            // Panics with String payload usually comes from timely here.
-            tracked_err::<PyRuntimeError>(msg)
-        } else if let Some(msg) = panic_err.downcast_ref::<&str>() {
-            // Panic with &str payload, usually from a direct call to `panic!`
-            // or `.expect`
-            tracked_err::<PyRuntimeError>(msg)
+    res.map_err(|panic_err| unwrap_worker_panic(py, panic_err))
+}
+
+/// Turn a caught worker panic payload into the `PyErr` it should surface
+/// as in the calling (or awaiting) Python thread.
+///
+/// Shared between [`run_main`] and [`run_main_async`]'s
+/// [`RunningFlow::await_result`] so a panic raised by a dataflow worker
+/// looks the same whether the dataflow was run synchronously or polled
+/// from the background.
+pub(crate) fn unwrap_worker_panic(py: Python, panic_err: Box<dyn Any + Send>) -> PyErr {
+    // The worker panicked.
+    // Print an empty line to separate rust panick message from the rest.
+    eprintln!("");
+    if let Some(err) = panic_err.downcast_ref::<PyErr>() {
+        // Special case for keyboard interrupt.
+        if err.get_type(py).is(PyType::new::<PyKeyboardInterrupt>(py)) {
+            tracked_err::<PyKeyboardInterrupt>(
+                "interrupt signal received, all processes have been shut down",
+            )
         } else {
-            // Give up trying to understand the error, and show the user
-            // a really helpful message.
-            // We could show the debug representation of `panic_err`, but
-            // it would just be `Any { .. }`
-            tracked_err::<PyRuntimeError>("unknown error")
+            // Panics with PyErr as payload should come from bytewax.
+            err.clone_ref(py)
         }
-    })
+    } else if let Some(msg) = panic_err.downcast_ref::<String>() {
+        // Panics with String payload usually comes from timely here.
+        tracked_err::<PyRuntimeError>(msg)
+    } else if let Some(msg) = panic_err.downcast_ref::<&str>() {
+        // Panic with &str payload, usually from a direct call to `panic!`
+        // or `.expect`
+        tracked_err::<PyRuntimeError>(msg)
+    } else {
+        // Give up trying to understand the error, and show the user
+        // a really helpful message.
+        // We could show the debug representation of `panic_err`, but
+        // it would just be `Any { .. }`
+        tracked_err::<PyRuntimeError>("unknown error")
+    }
+}
+
+/// Execute a dataflow on a background thread without blocking the
+/// calling Python thread.
+///
+/// Returns a [`RunningFlow`] handle immediately; call
+/// `RunningFlow.await_result()` to poll for completion, or
+/// `RunningFlow.join()` to block until it happens, and to re-raise any
+/// error the dataflow encountered. This
+/// is useful for interleaving a Bytewax dataflow with an asyncio event
+/// loop, or for driving several dataflows cooperatively in one
+/// interpreter.
+///
+/// >>> from bytewax.dataflow import Dataflow
+/// >>> from bytewax.inputs import TestingInputConfig
+/// >>> from bytewax.outputs import StdOutputConfig
+/// >>> flow = Dataflow()
+/// >>> flow.input("inp", TestingInputConfig(range(3)))
+/// >>> flow.capture(StdOutputConfig())
+/// >>> handle = run_main_async(flow)
+/// >>> handle.join()
+/// 0
+/// 1
+/// 2
+///
+/// Args:
+///
+///   flow: Dataflow to run.
+///
+///   epoch_interval (datetime.timedelta): System time length of each
+///       epoch. Defaults to 10 seconds.
+///
+///   recovery_config: State recovery config. See
+///       `bytewax.recovery`. If `None`, state will not be
+///       persisted.
+///
+///   driver: A `Driver` to share with the returned `RunningFlow`. If
+///       `None`, a fresh one is created; either way it's accessible
+///       through `RunningFlow.stop()`.
+///
+///   chaos_config: Opt-in deterministic fault injection; see
+///       `run_main`'s `chaos_config`. If `None` (the default), no
+///       faults are injected.
+///
+///   debug_mode: Opt-in interactive debugger; see `run_main`'s
+///       `debug_mode`. If `None` (the default), exceptions propagate
+///       as they do today.
+///
+/// Set `BYTEWAX_OTEL_ENDPOINT`, same as `run_main`, to export traces
+/// for this run.
+#[pyfunction(
+    flow,
+    "*",
+    epoch_interval = "None",
+    recovery_config = "None",
+    driver = "None",
+    chaos_config = "None",
+    debug_mode = "None"
+)]
+#[pyo3(
+    text_signature = "(flow, *, epoch_interval, recovery_config, driver, chaos_config, debug_mode)"
+)]
+pub(crate) fn run_main_async(
+    py: Python,
+    flow: Py<Dataflow>,
+    epoch_interval: Option<EpochInterval>,
+    recovery_config: Option<Py<RecoveryConfig>>,
+    driver: Option<Driver>,
+    chaos_config: Option<ChaosConfig>,
+    debug_mode: Option<DebugConfig>,
+) -> PyResult<RunningFlow> {
+    tracing::info!("Running single worker on single process in the background");
+    let driver = driver.unwrap_or_else(Driver::unattached);
+    let worker_driver = driver.clone();
+    let _otel_guard = otel::init_from_env();
+
+    let join_handle = py
+        .allow_threads(move || {
+            thread::Builder::new()
+                .name("bytewax-run_main_async".to_string())
+                .spawn(move || {
+                    // Keep the exporter alive for the background
+                    // thread's whole run, same as `cluster_main_async`.
+                    let _otel_guard = _otel_guard;
+                    std::panic::catch_unwind(|| {
+                        timely::execute::execute_directly::<(), _>(move |worker| {
+                            // Root span for this worker's whole run, same as
+                            // `run_main` and `cluster_main_async`'s
+                            // `spawn_cluster_workers`. `proc_id` is always 0
+                            // here: `run_main_async`, like `run_main`, only
+                            // ever runs a single process.
+                            let worker_index = WorkerIndex(worker.index());
+                            let _root_span = tracing::info_span!(
+                                "bytewax_worker",
+                                proc_id = 0,
+                                worker_index = worker_index.0
+                            )
+                            .entered();
+
+                            let worker_runner = WorkerRunner::new(
+                                worker,
+                                worker_driver.interrupt_flag(),
+                                flow,
+                                epoch_interval
+                                    .unwrap_or(EpochInterval::new(Duration::from_secs(10))),
+                                recovery_config.unwrap_or(default_recovery_config()),
+                                chaos_config,
+                                debug_mode,
+                            );
+                            // Same panic-payload convention as `run_main`;
+                            // unwrapped again in `RunningFlow::await_result`.
+                            unwrap_any!(worker_runner.run().reraise("worker error"))
+                        })
+                    })
+                })
+        })
+        .raise::<PyRuntimeError>("error spawning background worker thread")?;
+
+    Ok(RunningFlow::new(join_handle, driver))
+}
+
+/// A worker's fatal [`PyErr`], captured before it panics.
+///
+/// `timely::execute::execute_from`'s `WorkerGuards::join()` only hands
+/// back `thread::Result<()>`, and by the time a panic payload crosses
+/// that join it's already been downcast to a bare `String` by Timely,
+/// losing the original exception and traceback. The closure that
+/// drives each worker's [`WorkerRunner`] (see [`spawn_cluster_workers`])
+/// shares one `Sender<WorkerFault>`, behind a `Mutex`, and sends one of
+/// these when `WorkerRunner::run` returns an error, right before
+/// panicking, so the thread that calls `guards.join()` can drain the
+/// matching [`std::sync::mpsc::Receiver`] afterward and re-raise the real
+/// exception instead of a generic "worker thread died" message.
+///
+/// Modeled on a labeled-result-over-a-channel pattern (rather than a
+/// bare `PyErr`) so the `worker_index` is available if a future caller
+/// wants to report which worker faulted first among several.
+struct WorkerFault {
+    worker_index: WorkerIndex,
+    err: PyErr,
+}
+
+/// Re-raise the first [`WorkerFault`] received on `fault_rx`, if any,
+/// instead of `fallback` (the generic message `guards.join()` alone
+/// can produce once Timely has cast the panic payload down to
+/// `String`).
+fn reraise_first_fault(fault_rx: &std::sync::mpsc::Receiver<WorkerFault>, fallback: PyErr) -> PyErr {
+    match fault_rx.try_recv() {
+        Ok(fault) => {
+            eprintln!("worker {} raised the following exception:", fault.worker_index.0);
+            Python::with_gil(|py| fault.err.clone_ref(py))
+        }
+        Err(_) => fallback,
+    }
+}
+
+/// Build the `timely` communication pipeline for a cluster process and
+/// spawn its worker threads.
+///
+/// Shared by [`cluster_main`] (which blocks on the returned guards)
+/// and [`cluster_main_async`] (which hands them to a background
+/// thread instead), so the two entry points can't drift on how a
+/// process's workers are actually started.
+#[allow(clippy::too_many_arguments)]
+fn spawn_cluster_workers(
+    flow: Py<Dataflow>,
+    addresses: Option<Vec<String>>,
+    proc_id: usize,
+    epoch_interval: Option<EpochInterval>,
+    recovery_config: Option<Py<RecoveryConfig>>,
+    worker_count_per_proc: usize,
+    chaos_config: Option<ChaosConfig>,
+    debug_mode: Option<DebugConfig>,
+    should_shutdown: Arc<AtomicBool>,
+    fault_tx: Sender<WorkerFault>,
+    otel_parent: Option<opentelemetry::Context>,
+) -> PyResult<timely::execute::WorkerGuards<()>> {
+    let addresses = addresses.unwrap_or_default();
+    let (builders, other) = if addresses.is_empty() {
+        timely::CommunicationConfig::Process(worker_count_per_proc)
+    } else {
+        timely::CommunicationConfig::Cluster {
+            threads: worker_count_per_proc,
+            process: proc_id,
+            addresses,
+            report: false,
+            log_fn: Box::new(|_| None),
+        }
+    }
+    .try_build()
+    .raise::<PyRuntimeError>("error building timely communication pipeline")?;
+
+    // `timely::execute::execute_from` requires its closure be `Sync`
+    // (it's called concurrently from every worker thread), but
+    // `mpsc::Sender` isn't; a `Mutex` around the one shared `Sender`
+    // makes it so, same as other cross-worker shared state here
+    // (`DEBUG_GATE` in `debug.rs`).
+    let fault_tx = Arc::new(std::sync::Mutex::new(fault_tx));
+
+    timely::execute::execute_from::<_, (), _>(
+        builders,
+        other,
+        timely::WorkerConfig::default(),
+        move |worker| {
+            let worker_index = WorkerIndex(worker.index());
+            // Root span for this worker's whole run; every span
+            // `WorkerRunner::run` opens per epoch nests under this one
+            // via `tracing`'s thread-local span stack, no explicit
+            // threading needed. When `otel_parent` is set (this
+            // process was launched by `spawn_cluster`, see
+            // `execution::otel`), this span in turn nests under the
+            // parent process's span, so a collector sees one trace for
+            // the whole cluster run instead of one per process.
+            let root_span =
+                tracing::info_span!("bytewax_worker", proc_id, worker_index = worker_index.0);
+            if let Some(cx) = otel_parent.clone() {
+                root_span.set_parent(cx);
+            }
+            let _root_span = root_span.entered();
+
+            let worker_runner = WorkerRunner::new(
+                worker,
+                &should_shutdown,
+                flow.clone(),
+                epoch_interval
+                    .clone()
+                    .unwrap_or(EpochInterval::new(Duration::from_secs(10))),
+                recovery_config.clone().unwrap_or(default_recovery_config()),
+                chaos_config.clone(),
+                debug_mode.clone(),
+            );
+            if let Err(err) = worker_runner.run() {
+                // Stash a clone on `fault_tx` before panicking: once this
+                // crosses `guards.join()` in the caller, Timely has
+                // already cast the payload down to a `String`.
+                let reported = Python::with_gil(|py| err.clone_ref(py));
+                let _ = fault_tx.lock().unwrap().send(WorkerFault {
+                    worker_index,
+                    err: reported,
+                });
+                std::panic::panic_any(err);
+            }
+        },
+    )
+    .raise::<PyRuntimeError>("error during execution")
 }
 
 /// Execute a dataflow in the current process as part of a cluster.
@@ -635,6 +1166,36 @@ pub(crate) fn run_main(
 ///
 ///   worker_count_per_proc: Number of worker threads to start on
 ///       each process.
+///
+///   driver: A `Driver` to stop this dataflow from another thread by
+///       calling its `stop()` method. If `None`, a `Driver` is still
+///       created internally but can't be retrieved since `cluster_main`
+///       blocks until the dataflow finishes.
+///
+///   chaos_config: Opt-in deterministic fault injection; see
+///       `run_main`'s `chaos_config`. If `None` (the default), no
+///       faults are injected.
+///
+///   debug_mode: Opt-in interactive debugger; see `run_main`'s
+///       `debug_mode` for what it does. Workers across this process
+///       (but not other processes in the cluster) take turns in the
+///       post-mortem hook, one at a time. If `None` (the default),
+///       exceptions propagate as they do today.
+///
+/// Set `BYTEWAX_OTEL_ENDPOINT`, same as `run_main`, to export traces
+/// for this process's workers. When this process was itself launched
+/// by `spawn_cluster` (i.e. `__BYTEWAX_PROC_ID` is set), its root span
+/// re-attaches to the launching process's span, so the whole cluster's
+/// spans land in the collector as one trace.
+///
+///   cluster_config / self_addr: An alternative to `addresses`/
+///       `proc_id` for deployments that don't know every process's
+///       address up front (e.g. identical pods in a Kubernetes
+///       `Deployment`): pass a `ClusterConfig` plus this process's own
+///       `host:port` as `self_addr`, leaving `addresses`/`proc_id` as
+///       `None`, and this process registers `self_addr` and blocks
+///       until every peer has rendezvoused before continuing. See
+///       `ClusterConfig`.
 #[pyfunction(
     flow,
     addresses,
@@ -642,42 +1203,48 @@ pub(crate) fn run_main(
     "*",
     epoch_interval = "None",
     recovery_config = "None",
-    worker_count_per_proc = "1"
+    worker_count_per_proc = "1",
+    driver = "None",
+    chaos_config = "None",
+    debug_mode = "None",
+    cluster_config = "None",
+    self_addr = "None"
 )]
 #[pyo3(
-    text_signature = "(flow, addresses, proc_id, *, epoch_interval, recovery_config, worker_count_per_proc)"
+    text_signature = "(flow, addresses, proc_id, *, epoch_interval, recovery_config, worker_count_per_proc, driver, chaos_config, debug_mode, cluster_config, self_addr)"
 )]
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn cluster_main(
     py: Python,
     flow: Py<Dataflow>,
     addresses: Option<Vec<String>>,
-    proc_id: usize,
+    proc_id: Option<usize>,
     epoch_interval: Option<EpochInterval>,
     recovery_config: Option<Py<RecoveryConfig>>,
     worker_count_per_proc: usize,
+    driver: Option<Driver>,
+    chaos_config: Option<ChaosConfig>,
+    debug_mode: Option<DebugConfig>,
+    cluster_config: Option<ClusterConfig>,
+    self_addr: Option<String>,
 ) -> PyResult<()> {
+    let (addresses, proc_id) = py.allow_threads(|| {
+        cluster::resolve_addresses(addresses, proc_id, cluster_config, self_addr)
+    })?;
+
     tracing::info!(
         "Running {} workers on process {}",
         worker_count_per_proc,
         proc_id
     );
+    let driver = driver.unwrap_or_else(Driver::unattached);
+    // `_otel_guard` just needs to outlive `py.allow_threads` below, so
+    // the exporter (if `BYTEWAX_OTEL_ENDPOINT` is set) keeps flushing
+    // spans for the whole blocking call.
+    let _otel_guard = otel::init_from_env();
+    let otel_parent = otel::attach_from_env();
     py.allow_threads(move || {
-        let addresses = addresses.unwrap_or_default();
-        let (builders, other) = if addresses.is_empty() {
-            timely::CommunicationConfig::Process(worker_count_per_proc)
-        } else {
-            timely::CommunicationConfig::Cluster {
-                threads: worker_count_per_proc,
-                process: proc_id,
-                addresses,
-                report: false,
-                log_fn: Box::new(|_| None),
-            }
-        }
-        .try_build()
-        .raise::<PyRuntimeError>("error building timely communication pipeline")?;
-
-        let should_shutdown = Arc::new(AtomicBool::new(false));
+        let should_shutdown = driver.interrupt_flag().clone();
         let should_shutdown_w = should_shutdown.clone();
         let should_shutdown_p = should_shutdown.clone();
 
@@ -716,24 +1283,20 @@ pub(crate) fn cluster_main(
             })?)?);
         };
 
-        let guards = timely::execute::execute_from::<_, (), _>(
-            builders,
-            other,
-            timely::WorkerConfig::default(),
-            move |worker| {
-                let worker_runner = WorkerRunner::new(
-                    worker,
-                    &should_shutdown_w,
-                    flow.clone(),
-                    epoch_interval
-                        .clone()
-                        .unwrap_or(EpochInterval::new(Duration::from_secs(10))),
-                    recovery_config.clone().unwrap_or(default_recovery_config()),
-                );
-                unwrap_any!(worker_runner.run())
-            },
-        )
-        .raise::<PyRuntimeError>("error during execution")?;
+        let (fault_tx, fault_rx) = channel();
+        let guards = spawn_cluster_workers(
+            flow,
+            addresses,
+            proc_id,
+            epoch_interval,
+            recovery_config,
+            worker_count_per_proc,
+            chaos_config,
+            debug_mode,
+            should_shutdown_w,
+            fault_tx,
+            otel_parent,
+        )?;
 
         // Recreating what Python does in Thread.join() to "block"
         // but also check interrupt handlers.
@@ -750,13 +1313,17 @@ pub(crate) fn cluster_main(
             })?;
         }
         for maybe_worker_panic in guards.join() {
-            // TODO: See if we can PR Timely to not cast panic info to
-            // String. Then we could re-raise Python exception in main
-            // thread and not need to print in panic::set_hook above,
-            // although we still need it to tell the other workers to
-            // do graceful shutdown.
+            // Timely casts the panic payload down to a bare `String`
+            // by the time it crosses `guards.join()`, so reach for the
+            // real `PyErr` one of the workers stashed on `fault_rx`
+            // before panicking, rather than surfacing a generic
+            // message; we still need the panic hook above to tell the
+            // other workers to do a graceful shutdown.
             maybe_worker_panic.map_err(|_| {
-                tracked_err::<PyRuntimeError>("Worker thread died; look for errors above")
+                reraise_first_fault(
+                    &fault_rx,
+                    tracked_err::<PyRuntimeError>("Worker thread died; look for errors above"),
+                )
             })?;
         }
 
@@ -764,11 +1331,113 @@ pub(crate) fn cluster_main(
     })
 }
 
+/// Execute a dataflow in the current process as part of a cluster, on
+/// a background thread, without blocking the calling Python thread.
+///
+/// Returns a [`RunningFlow`] handle immediately, same as
+/// `run_main_async`; call `RunningFlow.await_result()` to poll for
+/// completion, `RunningFlow.join()` to block until it happens, and
+/// `RunningFlow.stop()` to ask this process's workers to drain and
+/// exit at the next epoch boundary. See `cluster_main` for the argument
+/// descriptions; this takes the same ones minus `driver` (use the
+/// returned handle instead) and minus `cluster_config`/`self_addr`
+/// (there is no rendezvous here, and `proc_id` is required), plus the
+/// caveat that, unlike `cluster_main`, it does not
+/// poll `Python::check_signals` itself, since it isn't blocking the
+/// thread that would receive the signal.
+#[pyfunction(
+    flow,
+    addresses,
+    proc_id,
+    "*",
+    epoch_interval = "None",
+    recovery_config = "None",
+    worker_count_per_proc = "1",
+    chaos_config = "None",
+    debug_mode = "None"
+)]
+#[pyo3(
+    text_signature = "(flow, addresses, proc_id, *, epoch_interval, recovery_config, worker_count_per_proc, chaos_config, debug_mode)"
+)]
+pub(crate) fn cluster_main_async(
+    py: Python,
+    flow: Py<Dataflow>,
+    addresses: Option<Vec<String>>,
+    proc_id: usize,
+    epoch_interval: Option<EpochInterval>,
+    recovery_config: Option<Py<RecoveryConfig>>,
+    worker_count_per_proc: usize,
+    chaos_config: Option<ChaosConfig>,
+    debug_mode: Option<DebugConfig>,
+) -> PyResult<RunningFlow> {
+    tracing::info!(
+        "Running {} workers on process {} in the background",
+        worker_count_per_proc,
+        proc_id
+    );
+    let driver = Driver::unattached();
+    let worker_driver = driver.clone();
+    let _otel_guard = otel::init_from_env();
+    let otel_parent = otel::attach_from_env();
+
+    let join_handle = py
+        .allow_threads(move || {
+            thread::Builder::new()
+                .name("bytewax-cluster_main_async".to_string())
+                .spawn(move || {
+                    // Keep the exporter alive for the background
+                    // thread's whole run, same as `cluster_main`.
+                    let _otel_guard = _otel_guard;
+                    std::panic::catch_unwind(|| {
+                        let (fault_tx, fault_rx) = channel();
+                        let guards = match spawn_cluster_workers(
+                            flow,
+                            addresses,
+                            proc_id,
+                            epoch_interval,
+                            recovery_config,
+                            worker_count_per_proc,
+                            chaos_config,
+                            debug_mode,
+                            worker_driver.interrupt_flag().clone(),
+                            fault_tx,
+                            otel_parent,
+                        ) {
+                            Ok(guards) => guards,
+                            Err(err) => std::panic::panic_any(err),
+                        };
+                        for maybe_worker_panic in guards.join() {
+                            unwrap_any!(maybe_worker_panic.map_err(|_| {
+                                reraise_first_fault(
+                                    &fault_rx,
+                                    tracked_err::<PyRuntimeError>(
+                                        "Worker thread died; look for errors above",
+                                    ),
+                                )
+                            }));
+                        }
+                    })
+                })
+        })
+        .raise::<PyRuntimeError>("error spawning background worker thread")?;
+
+    Ok(RunningFlow::new(join_handle, driver))
+}
+
+/// How long, by default, `spawn_cluster` waits for a child process to
+/// exit on its own after sending `SIGTERM` before escalating to
+/// `kill()`.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Spawns a cluster on a single machine.
 /// This is only supposed to be used through `python -m bytewax.run`,
 /// and not directly called inside python code.
 ///
 /// See `python -m bytewax.run --help` for more info
+///
+/// `shutdown_timeout`, in seconds, bounds how long a `Ctrl-C` waits
+/// for each spawned process to exit after `SIGTERM` before sending it
+/// `SIGKILL`. Defaults to [`DEFAULT_SHUTDOWN_TIMEOUT`].
 #[pyfunction(
     flow,
     "*",
@@ -777,7 +1446,8 @@ pub(crate) fn cluster_main(
     process_id = "None",
     addresses = "None",
     epoch_interval = "None",
-    recovery_config = "None"
+    recovery_config = "None",
+    shutdown_timeout = "None"
 )]
 pub(crate) fn spawn_cluster(
     py: Python,
@@ -788,6 +1458,7 @@ pub(crate) fn spawn_cluster(
     addresses: Option<Vec<String>>,
     epoch_interval: Option<f64>,
     recovery_config: Option<Py<RecoveryConfig>>,
+    shutdown_timeout: Option<f64>,
 ) -> PyResult<()> {
     if !is_in_bytewax_run(py)? {
         return Err(tracked_err::<PyRuntimeError>(
@@ -810,10 +1481,15 @@ pub(crate) fn spawn_cluster(
             py,
             flow,
             addresses,
-            proc_id,
+            Some(proc_id),
             epoch_interval,
             recovery_config,
             workers_per_process.unwrap_or(1),
+            None,
+            None,
+            None,
+            None,
+            None,
         )
     } else {
         let proc_id = std::env::var("__BYTEWAX_PROC_ID").ok();
@@ -822,7 +1498,7 @@ pub(crate) fn spawn_cluster(
         let workers_per_process = workers_per_process.unwrap_or(1);
 
         if processes == 1 && workers_per_process == 1 {
-            run_main(py, flow, epoch_interval, recovery_config)
+            run_main(py, flow, epoch_interval, recovery_config, None, None, None)
         } else {
             let addresses = (0..processes)
                 .map(|proc_id| format!("localhost:{}", proc_id as u64 + 2101))
@@ -833,12 +1509,25 @@ pub(crate) fn spawn_cluster(
                     py,
                     flow,
                     Some(addresses),
-                    proc_id.parse().unwrap(),
+                    Some(proc_id.parse().unwrap()),
                     epoch_interval,
                     recovery_config,
                     workers_per_process,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
                 )?;
             } else {
+                // This process only supervises the others; entering a
+                // root span here (rather than per-worker, as
+                // `cluster_main` does) is what `inject_into_env` below
+                // propagates to each child, so the whole cluster's
+                // spans nest under one top-level "bytewax_run" span.
+                let _otel_guard = otel::init_from_env();
+                let _root_span = tracing::info_span!("bytewax_run", processes).entered();
+
                 let mut server_rt = None;
                 // Initialize the tokio runtime for the webserver if we needed.
                 if std::env::var("BYTEWAX_DATAFLOW_API_ENABLED").is_ok() {
@@ -849,11 +1538,16 @@ pub(crate) fn spawn_cluster(
                 let mut ps: Vec<_> = (0..processes)
                     .map(|proc_id| {
                         let mut args = std::env::args();
-                        Command::new(args.next().unwrap())
+                        let mut command = Command::new(args.next().unwrap());
+                        command
                             .env("__BYTEWAX_PROC_ID", proc_id.to_string())
-                            .args(args.collect::<Vec<String>>())
-                            .spawn()
-                            .unwrap()
+                            .args(args.collect::<Vec<String>>());
+                        // So each child's `cluster_main` re-attaches to
+                        // this process's span (if `BYTEWAX_OTEL_ENDPOINT`
+                        // is set) and the whole cluster shows up as one
+                        // trace; a no-op otherwise.
+                        otel::inject_into_env(&mut command);
+                        command.spawn().unwrap()
                     })
                     .collect();
                 loop {
@@ -863,8 +1557,37 @@ pub(crate) fn spawn_cluster(
 
                     let check = Python::with_gil(|py| py.check_signals());
                     if check.is_err() {
+                        // Ask nicely first, so each process can flush
+                        // recovery state and close its sources/sinks,
+                        // same as the graceful path `Driver::stop()`
+                        // takes; only escalate to `kill()` (`SIGKILL`,
+                        // no cleanup) for stragglers still alive once
+                        // `shutdown_timeout` elapses.
+                        for process in ps.iter_mut() {
+                            // SAFETY: `process.id()` is the pid of a
+                            // child we spawned and haven't reaped yet.
+                            // `std::process::Child` has no `SIGTERM`-only
+                            // equivalent of `kill()` (which always sends
+                            // `SIGKILL`), so signal it directly.
+                            unsafe {
+                                libc::kill(process.id() as libc::pid_t, libc::SIGTERM);
+                            }
+                        }
+                        let deadline = Instant::now()
+                            + shutdown_timeout
+                                .map(Duration::from_secs_f64)
+                                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+                        while Instant::now() < deadline
+                            && ps
+                                .iter_mut()
+                                .any(|process| matches!(process.try_wait(), Ok(None)))
+                        {
+                            thread::sleep(Duration::from_millis(50));
+                        }
                         for process in ps.iter_mut() {
-                            process.kill()?;
+                            if matches!(process.try_wait(), Ok(None)) {
+                                process.kill()?;
+                            }
                         }
                         // Don't forget to shutdown the server runtime.
                         // If we just drop the runtime, it will wait indefinitely
@@ -888,7 +1611,14 @@ pub(crate) fn spawn_cluster(
 
 pub(crate) fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(run_main, m)?)?;
+    m.add_function(wrap_pyfunction!(run_main_async, m)?)?;
     m.add_function(wrap_pyfunction!(cluster_main, m)?)?;
+    m.add_function(wrap_pyfunction!(cluster_main_async, m)?)?;
     m.add_function(wrap_pyfunction!(spawn_cluster, m)?)?;
+    m.add_class::<handle::RunningFlow>()?;
+    m.add_class::<handle::Driver>()?;
+    m.add_class::<chaos::ChaosConfig>()?;
+    m.add_class::<debug::DebugConfig>()?;
+    m.add_class::<cluster::ClusterConfig>()?;
     Ok(())
 }
@@ -0,0 +1,126 @@
+//! Distributed tracing: one OTLP trace per cluster run instead of one
+//! per process.
+//!
+//! [`init_from_env`] is gated entirely on `BYTEWAX_OTEL_ENDPOINT` being
+//! set; when it isn't, this module does nothing and `tracing::info!`
+//! et al. behave exactly as they do today (printed by whatever
+//! subscriber the embedding application installed, or dropped if none
+//! is). When it is, every process in the cluster exports its spans to
+//! that OTLP collector endpoint, and [`inject_into_env`]/
+//! [`attach_from_env`] thread the root span's context through
+//! `spawn_cluster`'s `__BYTEWAX_PROC_ID`-style child-process
+//! relaunch so the per-process traces a collector receives stitch back
+//! together into the one trace a user started, the same way
+//! LiberTEM's pipelined executor propagates a `SpanContext` into each
+//! worker and has it call `attach_to_parent`.
+
+use std::collections::HashMap;
+
+use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::Context;
+use tracing_subscriber::prelude::*;
+
+/// The env var a child process launched by `spawn_cluster` reads to
+/// re-attach to the parent's trace; analogous to `__BYTEWAX_PROC_ID`.
+const TRACE_CONTEXT_ENV_VAR: &str = "__BYTEWAX_TRACE_CONTEXT";
+
+/// Holds the OTLP exporter alive for the life of the process; dropping
+/// it flushes any spans still buffered and tears the pipeline down.
+///
+/// Returned by [`init_from_env`] so the caller (`cluster_main`/
+/// `run_main`) can keep it alive for as long as the dataflow runs by
+/// binding it to a local, the same way `_server_rt` keeps the
+/// webserver's tokio runtime alive.
+pub(crate) struct OtelGuard;
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+}
+
+/// If `BYTEWAX_OTEL_ENDPOINT` is set, install a `tracing_subscriber`
+/// layer that exports every span to it over OTLP and return a guard
+/// that flushes on drop; otherwise a no-op, so the default path (no
+/// env var) behaves exactly as it did before this module existed.
+///
+/// Like `crate::logging::init_logging`, this installs a process-wide
+/// `tracing` subscriber and so can only meaningfully be called once;
+/// calling both in the same process is not supported.
+pub(crate) fn init_from_env() -> Option<OtelGuard> {
+    let endpoint = std::env::var("BYTEWAX_OTEL_ENDPOINT").ok()?;
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .install_batch(opentelemetry::runtime::Tokio)
+        .ok()?;
+
+    let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    // Ignored: if a subscriber is already installed (e.g. a Python
+    // embedder set one up itself), leave it in place rather than
+    // panicking the whole dataflow over telemetry wiring.
+    let _ = tracing_subscriber::registry().with(otel_layer).try_init();
+
+    Some(OtelGuard)
+}
+
+struct EnvMapInjector<'a>(&'a mut HashMap<String, String>);
+
+impl Injector for EnvMapInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+struct EnvMapExtractor<'a>(&'a HashMap<String, String>);
+
+impl Extractor for EnvMapExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
+
+/// Serialize the calling thread's current span context (its W3C
+/// `traceparent`) onto `command`'s environment, so a child process
+/// spawned from it can [`attach_from_env`] and have its own spans
+/// appear as descendants of this one in the collector.
+///
+/// A no-op (sets nothing) if [`init_from_env`] was never called, since
+/// there's no trace for a child to join.
+pub(crate) fn inject_into_env(command: &mut std::process::Command) {
+    let cx = tracing::Span::current().context();
+    if !cx.span().span_context().is_valid() {
+        return;
+    }
+
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(&cx, &mut EnvMapInjector(&mut carrier));
+    if let Ok(encoded) = serde_json::to_string(&carrier) {
+        command.env(TRACE_CONTEXT_ENV_VAR, encoded);
+    }
+}
+
+/// Read back a context [`inject_into_env`] stashed in this process's
+/// environment (set by the parent `spawn_cluster` that launched it)
+/// and return it, so the caller can set it as its own root span's
+/// parent (`set_parent`) and have that span nest under the parent's
+/// trace.
+///
+/// Returns `None` if the env var isn't set (not a `spawn_cluster`
+/// child) or can't be decoded.
+pub(crate) fn attach_from_env() -> Option<Context> {
+    let encoded = std::env::var(TRACE_CONTEXT_ENV_VAR).ok()?;
+    let carrier: HashMap<String, String> = serde_json::from_str(&encoded).ok()?;
+    Some(TraceContextPropagator::new().extract(&EnvMapExtractor(&carrier)))
+}
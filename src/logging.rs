@@ -0,0 +1,207 @@
+//! Routing Bytewax's internal `tracing` telemetry to a Python
+//! callback.
+//!
+//! [`init_logging`] installs a [`PyLoggingLayer`] that flattens every
+//! event Bytewax emits (level, target, fields, worker index,
+//! current epoch — the latter two via thread-locals set from
+//! [`crate::execution`]) into a plain dict and hands it to a
+//! user-supplied Python callable, so an embedding application can
+//! route Bytewax's telemetry into its own logging/metrics pipeline
+//! instead of only stderr. Spans themselves are not forwarded.
+//!
+//! `on_event` runs synchronously on whatever thread emitted the event
+//! (often a Timely worker thread mid-dataflow), so it must not acquire
+//! the GIL or call into Python directly: a slow callback would stall
+//! the dataflow. Instead it pushes onto a bounded channel that a
+//! single dispatcher thread drains, holding the GIL only there. If the
+//! channel is full, the event is dropped and counted in
+//! [`DROPPED_EVENTS`] rather than blocking the worker.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+
+use crate::errors::{tracked_err, PythonException};
+
+/// How many captured events the dispatcher channel holds before new
+/// ones are dropped instead of queued.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Count of events dropped because the dispatcher couldn't keep up
+/// with the rate events were captured at. Exposed to Python via
+/// [`dropped_log_event_count`] as a cheap backpressure signal.
+static DROPPED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // Set once per worker thread by `crate::execution::build_production_dataflow`.
+    static CURRENT_WORKER: Cell<Option<usize>> = const { Cell::new(None) };
+    // Updated as records flow through `crate::execution::tap_epoch`.
+    static CURRENT_EPOCH: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Record which worker this thread is building/running a dataflow
+/// for, so captured events on this thread are tagged with it.
+pub(crate) fn set_current_worker(worker_index: usize) {
+    CURRENT_WORKER.with(|cell| cell.set(Some(worker_index)));
+}
+
+/// Record the epoch the current thread is processing, so captured
+/// events on this thread are tagged with it.
+pub(crate) fn set_current_epoch(epoch: u64) {
+    CURRENT_EPOCH.with(|cell| cell.set(Some(epoch)));
+}
+
+/// One flattened `tracing` event, ready to become a Python dict on the
+/// dispatcher thread.
+struct CapturedEvent {
+    level: &'static str,
+    target: String,
+    message: Option<String>,
+    fields: Vec<(String, String)>,
+    worker_index: Option<usize>,
+    epoch: Option<u64>,
+}
+
+/// Collects a `tracing::Event`'s fields via the `Visit` trait, which
+/// is how `tracing` hands out untyped field values without forcing
+/// every field to implement some common trait up front.
+#[derive(Default)]
+struct FieldCollector {
+    message: Option<String>,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldCollector {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{value:?}"));
+        } else {
+            self.fields
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+}
+
+/// The `tracing_subscriber` layer [`init_logging`] installs.
+///
+/// `debug` gates whether `DEBUG`/`TRACE` events are even collected; it
+/// exists as a cheap per-event filter so a non-debug run doesn't pay
+/// to format and channel-send events nobody asked for (the existing
+/// `PeriodicSpan` heartbeat is emitted at `TRACE`, see
+/// `crate::execution`).
+struct PyLoggingLayer {
+    sender: SyncSender<CapturedEvent>,
+    debug: bool,
+}
+
+impl<S: Subscriber> Layer<S> for PyLoggingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if !self.debug && (level == Level::DEBUG || level == Level::TRACE) {
+            return;
+        }
+
+        let mut collector = FieldCollector::default();
+        event.record(&mut collector);
+
+        let captured = CapturedEvent {
+            level: level.as_str(),
+            target: event.metadata().target().to_string(),
+            message: collector.message,
+            fields: collector.fields,
+            worker_index: CURRENT_WORKER.with(Cell::get),
+            epoch: CURRENT_EPOCH.with(Cell::get),
+        };
+
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(captured) {
+            DROPPED_EVENTS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Drain `receiver`, turning each [`CapturedEvent`] into a dict and
+/// calling `callback` with it. Holds the GIL only for the duration of
+/// each call, not while waiting for the next event.
+fn run_dispatcher(callback: Py<PyAny>, receiver: Receiver<CapturedEvent>) {
+    for captured in receiver {
+        Python::with_gil(|py| {
+            let dict = PyDict::new(py);
+            let build_dict = || -> PyResult<()> {
+                dict.set_item("level", captured.level)?;
+                dict.set_item("target", &captured.target)?;
+                dict.set_item("message", &captured.message)?;
+                dict.set_item("fields", captured.fields.into_py(py))?;
+                dict.set_item("worker_index", captured.worker_index)?;
+                dict.set_item("epoch", captured.epoch)?;
+                Ok(())
+            };
+            if let Err(err) = build_dict().and_then(|()| callback.call1(py, (dict,)).map(|_| ())) {
+                // The dispatcher thread has nowhere to propagate this
+                // to; printing is the same fallback `run_main`'s panic
+                // hook uses for un-routable errors.
+                err.print(py);
+            }
+        });
+    }
+}
+
+/// Install a `tracing_subscriber` layer that forwards Bytewax's
+/// internal event telemetry to `callback`; span opens and closes are
+/// not forwarded.
+///
+/// `callback` is called with a single dict argument on a dedicated
+/// dispatcher thread (never from a Timely worker thread), with keys
+/// `level`, `target`, `message`, `fields` (a list of `(name, repr)`
+/// pairs), `worker_index`, and `epoch` (the latter two `None` outside
+/// of dataflow execution). `epoch` is only tracked when the dataflow
+/// runs with `debug_mode` or `chaos_config` set; otherwise it is `None`
+/// even during execution. If `callback` can't keep up, events are
+/// dropped rather than queued without bound; see
+/// `dropped_log_event_count`.
+///
+/// `debug`, when `True`, also captures `DEBUG`/`TRACE`-level events,
+/// including the periodic dataflow-execution heartbeat.
+///
+/// Can only be called once per process; call it before `run_main`/
+/// `cluster_main`/`spawn_cluster`.
+#[pyfunction]
+#[pyo3(text_signature = "(callback, debug)")]
+pub(crate) fn init_logging(callback: Py<PyAny>, debug: bool) -> PyResult<()> {
+    let (sender, receiver) = sync_channel(CHANNEL_CAPACITY);
+
+    thread::Builder::new()
+        .name("bytewax-logging-dispatcher".to_string())
+        .spawn(move || run_dispatcher(callback, receiver))
+        .raise::<PyRuntimeError>("error spawning logging dispatcher thread")?;
+
+    let layer = PyLoggingLayer { sender, debug };
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer)).map_err(
+        |err| tracked_err::<PyRuntimeError>(&format!("error installing tracing subscriber: {err}")),
+    )?;
+
+    Ok(())
+}
+
+/// Number of telemetry events dropped so far because the dispatcher
+/// channel was full (the `callback` passed to `init_logging` is
+/// running too slowly to keep up).
+#[pyfunction]
+#[pyo3(text_signature = "()")]
+pub(crate) fn dropped_log_event_count() -> u64 {
+    DROPPED_EVENTS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn register(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(init_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(dropped_log_event_count, m)?)?;
+    Ok(())
+}
@@ -41,6 +41,25 @@ macro_rules! unwrap_any {
 /// assert_eq!(res.start, 0);
 /// assert_eq!(res.end, Some(12));
 /// ```
+/// Add `=> $py_exc` (one of the exception types in [`crate::errors`], e.g.
+/// `RecoveryError`) to raise that typed, catchable exception via `?`
+/// instead of panicking, preserving the error's `Display` message and
+/// chaining its source as `__cause__` (see [`crate::errors::wrap_error`]).
+/// Use this in a `PyResult`-returning function, like a constructor or a
+/// pickle hook, where the error doesn't need to cross a worker thread
+/// boundary and a normal Python exception is preferable to a panic.
+///
+/// ```rust
+/// use bytewax::errors::RecoveryError;
+/// use bytewax::try_unwrap;
+///
+/// fn parse_epoch(s: &str) -> pyo3::PyResult<u64> {
+///     Ok(try_unwrap!(s.parse::<u64>() => RecoveryError))
+/// }
+///
+/// assert_eq!(parse_epoch("12").unwrap(), 12);
+/// assert!(parse_epoch("oops").is_err());
+/// ```
 macro_rules! try_unwrap {
     ($pyfunc:expr) => {
         // This would be the perfect use for the
@@ -48,6 +67,9 @@ macro_rules! try_unwrap {
         // feature.
         (|| $pyfunc)().unwrap_or_else(|err| std::panic::panic_any(err))
     };
+    ($pyfunc:expr => $py_exc:ty) => {
+        (|| $pyfunc)().map_err(|err| $crate::errors::wrap_error::<$py_exc>(&err))?
+    };
 }
 
 #[macro_export]
@@ -89,7 +111,195 @@ macro_rules! log_func {
 ///     }
 /// );
 /// ```
+///
+/// Add `serde,` right before `args` (see below) to pickle via a single
+/// `bincode`-serialized `PyBytes` blob instead of the per-field
+/// `HashMap<&str, Py<PyAny>>` the arm above builds. Use this for a
+/// struct whose fields don't all have a direct Python/pickle
+/// representation (nested enums, `Duration`s, ...) as long as `$struct`
+/// derives `serde::Serialize`/`Deserialize`; `bincode` only needs
+/// those, not `Clone`/`IntoPy` on every field.
+///
+/// ```rust
+/// // Example usage:
+/// use bytewax::add_pymethods;
+/// use pyo3::{pyclass, Python};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[pyclass(module = "bytewax.window", subclass)]
+/// #[pyo3(text_signature = "()")]
+/// struct WindowConfig;
+///
+/// #[pyclass(module="bytewax.config", extends=WindowConfig)]
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct CountWindow { count: u64 };
+///
+/// add_pymethods!(
+///     CountWindow,
+///     parent: WindowConfig,
+///     signature: (count),
+///     serde,
+///     args {
+///         count: u64 => 0
+///     }
+/// );
+/// ```
+///
+/// Add `version: N` (and, once a field has been renamed or added,
+/// `migrations { from 0: |dict| {...}, from 1: |dict| {...} }`) to the
+/// plain (non-`serde`) arm instead, to tag every pickle with the
+/// struct's current version and migrate an older one forward before
+/// extracting fields. Recovery stores can outlive the bytewax version
+/// that wrote them, so unpickling needs a forward-compatible upgrade
+/// path rather than a hard failure the moment a field is renamed; each
+/// `from V` closure takes the raw `&PyDict` for a pickle at version
+/// `V` and mutates it in place (filling in a renamed/defaulted field)
+/// so it looks like a version-`V + 1` pickle, and `__setstate__` runs
+/// the whole `from 0 -> from 1 -> ... -> N` chain starting at whatever
+/// version the pickle reports (0 if it predates this field, i.e. was
+/// written before `version` existed at all).
+///
+/// ```rust
+/// // Example usage:
+/// use bytewax::add_pymethods;
+/// use pyo3::{pyclass, types::PyDict, Python};
+///
+/// #[pyclass(module = "bytewax.window", subclass)]
+/// #[pyo3(text_signature = "()")]
+/// struct WindowConfig;
+///
+/// #[pyclass(module="bytewax.config", extends=WindowConfig)]
+/// #[derive(Clone)]
+/// struct TumblingWindow { length_seconds: u64 };
+///
+/// add_pymethods!(
+///     TumblingWindow,
+///     parent: WindowConfig,
+///     signature: (length_seconds),
+///     version: 1,
+///     migrations {
+///         // v0 pickled the field as `length` in minutes; v1 renamed
+///         // it to `length_seconds` and changed the unit.
+///         from 0: |dict: &PyDict| -> pyo3::PyResult<()> {
+///             let minutes: u64 = dict
+///                 .get_item("length")
+///                 .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+///                     "bad pickle contents for TumblingWindow version 0: missing field length"
+///                 ))?
+///                 .extract()?;
+///             dict.set_item("length_seconds", minutes * 60)?;
+///             Ok(())
+///         }
+///     },
+///     args {
+///         length_seconds: u64 => 0
+///     }
+/// );
+/// ```
+///
+/// Replace `signature`/`args` with `repr:`, `default:` and an
+/// enum-keyed `variants { ... }` block when `$struct` is a sum type -- a
+/// config that's naturally "one of N shapes" (e.g. a window-close
+/// policy that is either "at count N" or "at watermark") rather than
+/// one flat struct. PyO3 doesn't allow a data-carrying enum to
+/// `#[pyclass(extends = ...)]` directly, so `$struct` stays an ordinary
+/// tuple struct wrapping a plain Rust enum, `$repr`, that actually holds
+/// the variant and its fields (draw the shape of `$repr`'s variants the
+/// way PyO3's `#[derive(FromPyObject)]` enum support does: one
+/// braced variant per shape). This generates a `py_new(variant,
+/// **kwargs)` that builds the named variant from its fields (defaulting
+/// any field the caller omits), plus a `__getstate__`/`__setstate__`
+/// pair that round-trips through pickle via a `"variant"` discriminator
+/// instead of assuming there's only one shape of fields to save; an
+/// unrecognized `kwargs` key for the matched variant raises `TypeError`
+/// rather than silently falling back to that field's default.
+/// `default:` names the variant `__getnewargs__` rebuilds as the
+/// pre-`__setstate__` dummy instance (see the non-enum arms'
+/// `__getnewargs__` for why that hack is needed at all); it doesn't
+/// have to be the variant `self` actually holds, since `__setstate__`
+/// overwrites it immediately.
+///
+/// ```rust
+/// // Example usage:
+/// use bytewax::add_pymethods;
+/// use pyo3::{pyclass, Python};
+///
+/// #[pyclass(module = "bytewax.window", subclass)]
+/// #[pyo3(text_signature = "()")]
+/// struct WindowConfig;
+///
+/// #[derive(Clone)]
+/// enum WindowCloseConditionRepr {
+///     AtCount { count: u64 },
+///     AtWatermark {},
+/// }
+///
+/// #[pyclass(module = "bytewax.window", extends = WindowConfig)]
+/// #[derive(Clone)]
+/// struct WindowCloseCondition(WindowCloseConditionRepr);
+///
+/// add_pymethods!(
+///     WindowCloseCondition,
+///     parent: WindowConfig,
+///     repr: WindowCloseConditionRepr,
+///     default: AtWatermark,
+///     variants {
+///         AtCount { count: u64 => 0 },
+///         AtWatermark {}
+///     }
+/// );
+/// ```
 macro_rules! add_pymethods {(
+    $struct:ident,
+    parent: $parent:ident,
+    signature: $signature:tt,
+    serde,
+    args { $($arg:ident: $arg_type:ty => $default:expr),* }
+) => {
+    #[pyo3::pymethods]
+    impl $struct {
+        #[new]
+        #[pyo3(signature=$signature)]
+        pub(crate) fn py_new($($arg: $arg_type),*) -> (Self, $parent) {
+            (Self { $($arg),* }, $parent {})
+        }
+
+        /// Egregious hack because pickling assumes the type has "empty"
+        /// mutable objects; see the non-`serde` arm's `__getnewargs__`
+        /// for why.
+        #[allow(unused_parens)]
+        fn __getnewargs__(&self) -> ($($arg_type,) *) {
+            ($($default,) *)
+        }
+
+        /// Serialize the whole struct to a `bincode`-encoded `PyBytes`
+        /// blob, rather than converting each field through `IntoPy`
+        /// (see the non-`serde` `add_pymethods!` arm).
+        fn __getstate__(&self, py: pyo3::Python) -> pyo3::PyResult<pyo3::Py<pyo3::PyAny>> {
+            let bytes = bincode::serialize(self).map_err(|err| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "error pickling {}: {}",
+                    stringify!($struct),
+                    err
+                ))
+            })?;
+            Ok(pyo3::types::PyBytes::new(py, &bytes).into())
+        }
+
+        /// Unpickle from the bytes `__getstate__` produced above.
+        fn __setstate__(&mut self, state: &pyo3::PyAny) -> pyo3::PyResult<()> {
+            let bytes: &pyo3::types::PyBytes = state.downcast()?;
+            *self = bincode::deserialize(bytes.as_bytes()).map_err(|err| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "bad pickle contents for {}: {}",
+                    stringify!($struct),
+                    err
+                ))
+            })?;
+            Ok(())
+        }
+    }
+};(
     $struct:ident,
     parent: $parent:ident,
     signature: $signature:tt,
@@ -143,4 +353,215 @@ macro_rules! add_pymethods {(
             Ok(())
         }
     }
+};(
+    $struct:ident,
+    parent: $parent:ident,
+    signature: $signature:tt,
+    version: $version:literal,
+    migrations { $(from $from_version:literal : $migrate:expr),* $(,)? },
+    args { $($arg:ident: $arg_type:ty => $default:expr),* }
+) => {
+    #[pyo3::pymethods]
+    impl $struct {
+        #[new]
+        #[pyo3(signature=$signature)]
+        pub(crate) fn py_new($($arg: $arg_type),*) -> (Self, $parent) {
+            (Self { $($arg),* }, $parent {})
+        }
+
+        /// Return a representation of this class as a PyDict, tagged
+        /// with the struct's current `version` so a future version of
+        /// `__setstate__` knows how far to migrate it forward.
+        fn __getstate__(&self) -> std::collections::HashMap<&str, pyo3::Py<pyo3::PyAny>> {
+            pyo3::Python::with_gil(|py| {
+                std::collections::HashMap::from([
+                    ("type", pyo3::IntoPy::into_py(stringify!($struct), py)),
+                    ("version", pyo3::IntoPy::into_py($version, py)),
+                    $((stringify!($arg), pyo3::IntoPy::into_py(self.$arg.clone(), py))),*
+                ])
+            })
+        }
+
+        /// Egregious hack because pickling assumes the type has "empty"
+        /// mutable objects.
+        ///
+        /// Pickle always calls `__new__(*__getnewargs__())` but notice we
+        /// don't have access to the pickled `db_file_path` yet, so we
+        /// have to pass in some dummy string value that will be
+        /// overwritten by `__setstate__()` shortly.
+        #[allow(unused_parens)]
+        fn __getnewargs__(&self) -> ($($arg_type,) *) {
+            ($($default,) *)
+        }
+
+        /// Unpickle from a PyDict, first migrating it forward from
+        /// whatever version it reports (0 if it predates the
+        /// `version` key entirely) to `$version` by running the
+        /// `from 0 -> from 1 -> ...` chain over the raw dict, then
+        /// extracting fields same as the unversioned arm.
+        fn __setstate__(&mut self, state: &pyo3::PyAny) -> pyo3::PyResult<()> {
+            #[allow(unused_variables, unused_mut)]
+            let dict: &pyo3::types::PyDict = state.downcast()?;
+
+            #[allow(unused_mut)]
+            let mut version: u32 = dict
+                .get_item("version")
+                .and_then(|v| v.extract().ok())
+                .unwrap_or(0);
+            let detected_version = version;
+
+            $(
+            if version == $from_version {
+                let migrate: fn(&pyo3::types::PyDict) -> pyo3::PyResult<()> = $migrate;
+                migrate(dict)?;
+                version += 1;
+            }
+            )*
+
+            $(
+            self.$arg = dict
+                .get_item(stringify!($arg))
+                .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+                    format!(
+                        "bad pickle contents for {} (detected version {}): missing field {}",
+                        stringify!($struct), detected_version, stringify!($arg)
+                    )
+                ))?
+                .extract()?;
+            )*
+            Ok(())
+        }
+    }
+};(
+    $struct:ident,
+    parent: $parent:ident,
+    signature: $signature:tt,
+    version: $version:literal,
+    args { $($arg:ident: $arg_type:ty => $default:expr),* }
+) => {
+    add_pymethods!(
+        $struct,
+        parent: $parent,
+        signature: $signature,
+        version: $version,
+        migrations {},
+        args { $($arg: $arg_type => $default),* }
+    );
+};(
+    $struct:ident,
+    parent: $parent:ident,
+    repr: $repr:ident,
+    default: $default_variant:ident,
+    variants { $($variant:ident { $($arg:ident: $arg_type:ty => $default:expr),* $(,)? }),+ $(,)? }
+) => {
+    #[pyo3::pymethods]
+    impl $struct {
+        /// Build the named `variant` of `$repr` from `kwargs`; a field
+        /// the caller omits falls back to its default, same as the
+        /// `args` defaults in the non-enum arms above. Unlike those
+        /// arms, `kwargs` can't be checked by a `#[pyo3(signature=...)]`
+        /// since the set of valid names depends on `variant`, so this
+        /// rejects any key that isn't one of the matched variant's
+        /// field names itself, instead of silently defaulting it.
+        #[new]
+        #[pyo3(signature = (variant, **kwargs))]
+        pub(crate) fn py_new(
+            variant: &str,
+            kwargs: Option<&pyo3::types::PyDict>,
+        ) -> pyo3::PyResult<(Self, $parent)> {
+            let inner = match variant {
+                $(
+                stringify!($variant) => {
+                    let known: &[&str] = &[$(stringify!($arg)),*];
+                    if let Some(kwargs) = kwargs {
+                        for key in kwargs.keys() {
+                            let key: &str = key.extract()?;
+                            if !known.contains(&key) {
+                                return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                                    "{}({:?}, ...) got an unexpected keyword argument {:?}, expected one of: {}",
+                                    stringify!($struct),
+                                    variant,
+                                    key,
+                                    known.join(", "),
+                                )));
+                            }
+                        }
+                    }
+                    $repr::$variant {
+                        $($arg: kwargs
+                            .and_then(|d| d.get_item(stringify!($arg)))
+                            .map(|v| v.extract())
+                            .transpose()?
+                            .unwrap_or($default)),*
+                    }
+                },
+                )+
+                other => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown variant {:?} for {}, expected one of: {}",
+                    other,
+                    stringify!($struct),
+                    [$(stringify!($variant)),+].join(", "),
+                ))),
+            };
+            Ok((Self(inner), $parent {}))
+        }
+
+        /// Egregious hack because pickling assumes the type has "empty"
+        /// mutable objects; see the non-enum arms' `__getnewargs__` for
+        /// the general shape. `__new__` is called as
+        /// `__new__("$default_variant")` with no `kwargs`, building
+        /// `$default_variant` with every field defaulted;
+        /// `__setstate__` immediately overwrites it with the real
+        /// variant and fields.
+        fn __getnewargs__(&self) -> (&'static str,) {
+            (stringify!($default_variant),)
+        }
+
+        /// Return a representation of this class as a PyDict, with a
+        /// `"variant"` discriminator alongside the chosen variant's
+        /// fields so `__setstate__` knows which one to rebuild.
+        fn __getstate__(&self) -> std::collections::HashMap<&str, pyo3::Py<pyo3::PyAny>> {
+            pyo3::Python::with_gil(|py| match &self.0 {
+                $(
+                $repr::$variant { $($arg),* } => std::collections::HashMap::from([
+                    ("type", pyo3::IntoPy::into_py(stringify!($struct), py)),
+                    ("variant", pyo3::IntoPy::into_py(stringify!($variant), py)),
+                    $((stringify!($arg), pyo3::IntoPy::into_py($arg.clone(), py))),*
+                ]),
+                )+
+            })
+        }
+
+        /// Unpickle from a PyDict, dispatching on the `"variant"` key
+        /// `__getstate__` wrote to reconstruct the correct variant.
+        fn __setstate__(&mut self, state: &pyo3::PyAny) -> pyo3::PyResult<()> {
+            let dict: &pyo3::types::PyDict = state.downcast()?;
+            let variant: String = dict
+                .get_item("variant")
+                .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!(
+                    "bad pickle contents for {}: missing field variant", stringify!($struct)
+                )))?
+                .extract()?;
+            self.0 = match variant.as_str() {
+                $(
+                stringify!($variant) => $repr::$variant {
+                    $($arg: dict
+                        .get_item(stringify!($arg))
+                        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!(
+                            "bad pickle contents for {} variant {}: missing field {}",
+                            stringify!($struct), stringify!($variant), stringify!($arg)
+                        )))?
+                        .extract()?),*
+                },
+                )+
+                other => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "bad pickle contents for {}: unknown variant {:?}, expected one of: {}",
+                    stringify!($struct),
+                    other,
+                    [$(stringify!($variant)),+].join(", "),
+                ))),
+            };
+            Ok(())
+        }
+    }
 }}